@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use lazy_static::lazy_static;
+
+use crate::debug::get_data_dir;
+
+lazy_static! {
+    pub static ref BOOKMARKS_FILE: String = "bookmarks.toml".to_string();
+}
+
+#[derive(Debug, Default)]
+pub struct Bookmarks {
+    items: BTreeMap<char, PathBuf>,
+}
+
+fn get_file_path() -> PathBuf {
+    let dir = get_data_dir();
+    dir.join(BOOKMARKS_FILE.clone())
+}
+
+impl Bookmarks {
+    pub fn load() -> Self {
+        let path = get_file_path();
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Self::default(),
+        };
+
+        match toml::from_str::<BTreeMap<String, PathBuf>>(&content) {
+            Ok(items) => Self {
+                items: items
+                    .into_iter()
+                    .filter_map(|(k, v)| Some((k.chars().next()?, v)))
+                    .collect(),
+            },
+            Err(e) => {
+                tracing::error!("Could not parse bookmarks file {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self) {
+        let items: BTreeMap<String, &PathBuf> =
+            self.items.iter().map(|(k, v)| (k.to_string(), v)).collect();
+
+        let content = match toml::to_string(&items) {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::error!("Could not serialize bookmarks: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = std::fs::write(get_file_path(), content) {
+            tracing::error!("Could not save bookmarks: {}", e);
+        }
+    }
+
+    pub fn set(&mut self, key: char, path: PathBuf) {
+        self.items.insert(key, path);
+        self.save();
+    }
+
+    pub fn remove(&mut self, key: char) {
+        self.items.remove(&key);
+        self.save();
+    }
+
+    pub fn get(&self, key: char) -> Option<&PathBuf> {
+        self.items.get(&key)
+    }
+
+    pub fn get_key(&self, index: usize) -> Option<char> {
+        self.items.keys().nth(index).copied()
+    }
+
+    pub fn get_names(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .map(|(k, v)| format!("({}) {}", k, v.display()))
+            .collect()
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use directories::ProjectDirs;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::debug::PROJECT_NAME;
+
+lazy_static! {
+    pub static ref CONFIG_FOLDER: Option<PathBuf> =
+        std::env::var(format!("{}_CONFIG", PROJECT_NAME.clone()))
+            .ok()
+            .map(PathBuf::from);
+    pub static ref CONFIG_FILE: String = "config.toml".to_string();
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub layout: Layout,
+    pub openers: Vec<Opener>,
+    pub commands: Vec<CustomCommand>,
+    pub log_level: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    #[default]
+    Single,
+    Dual,
+    Miller,
+}
+
+impl Layout {
+    pub fn next(self) -> Self {
+        match self {
+            Layout::Single => Layout::Dual,
+            Layout::Dual => Layout::Miller,
+            Layout::Miller => Layout::Single,
+        }
+    }
+}
+
+/// A named shell template, see `shell::expand` for the available placeholders.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomCommand {
+    pub name: String,
+    pub command: String,
+    pub key: Option<char>,
+    #[serde(default)]
+    pub confirm: bool,
+    #[serde(default)]
+    pub interactive: bool,
+}
+
+/// A program used to open files, the selected path is appended as its last argument.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Opener {
+    pub name: Option<String>,
+    pub command: String,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub globs: Vec<String>,
+    #[serde(default)]
+    pub mime: Vec<String>,
+    #[serde(default)]
+    pub detached: bool,
+}
+
+impl Opener {
+    pub fn get_name(&self) -> String {
+        let name = self.name.clone().unwrap_or_else(|| self.command.clone());
+
+        if self.detached {
+            format!("{} (detached)", name)
+        } else {
+            name
+        }
+    }
+}
+
+pub fn get_config_file_path() -> PathBuf {
+    let dir = {
+        if let Some(pt) = CONFIG_FOLDER.clone() {
+            pt
+        } else if let Some(dir) = ProjectDirs::from("com", "dd", env!("CARGO_PKG_NAME")) {
+            dir.config_dir().to_path_buf()
+        } else {
+            PathBuf::from(".").join(".config")
+        }
+    };
+
+    dir.join(CONFIG_FILE.clone())
+}
+
+/// The config, or the defaults and the reason when the config file could not be parsed.
+pub fn load() -> (Config, Option<String>) {
+    let path = get_config_file_path();
+
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return (Config::default(), None),
+    };
+
+    match toml::from_str(&content) {
+        Ok(config) => (config, None),
+        Err(e) => {
+            tracing::error!("Could not parse config file {:?}: {}", path, e);
+            let message = format!("Could not parse config file {:?}: {}", path, e.message());
+            (Config::default(), Some(message))
+        }
+    }
+}
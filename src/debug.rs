@@ -1,15 +1,12 @@
-use std::{fs::File, path::PathBuf};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use color_eyre::{eyre::Context, Result};
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
+use regex::Regex;
 use tracing::Level;
-use tracing_appender::{non_blocking, non_blocking::WorkerGuard};
-use tracing_subscriber::{
-    fmt::time::{self},
-    EnvFilter,
-};
-use tracing_subscriber::fmt::time::OffsetTime;
 
 lazy_static! {
     pub static ref PROJECT_NAME: String = env!("CARGO_CRATE_NAME").to_uppercase().to_string();
@@ -19,20 +16,115 @@ lazy_static! {
             .map(PathBuf::from);
     pub static ref LOG_ENV: String = format!("{}_LOGLEVEL", PROJECT_NAME.clone());
     pub static ref LOG_FILE: String = format!("{}.log", env!("CARGO_PKG_NAME"));
+    static ref TIMESTAMP: Regex =
+        Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})$").unwrap();
 }
 
-pub fn get_dir_and_log_file_path() -> (PathBuf, PathBuf) {
-    let dir = {
-        if let Some(pt) = DATA_FOLDER.clone() {
-            pt
-        } else if let Some(dir) = ProjectDirs::from("com", "dd", env!("CARGO_PKG_NAME")) {
-            dir.data_local_dir().to_path_buf()
-        } else {
-            PathBuf::from(".").join(".data")
+/// Where the logs and the other data files are kept.
+pub fn get_data_dir() -> PathBuf {
+    if let Some(pt) = DATA_FOLDER.clone() {
+        pt
+    } else if let Some(dir) = ProjectDirs::from("com", "dd", env!("CARGO_PKG_NAME")) {
+        dir.data_local_dir().to_path_buf()
+    } else {
+        PathBuf::from(".").join(".data")
+    }
+}
+
+const LOG_TAIL_BYTES: u64 = 256 * 1024;
+
+/// The newest of the rotated log files.
+pub fn get_latest_log_file_path() -> Option<PathBuf> {
+    let dir = get_data_dir();
+
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .starts_with(LOG_FILE.as_str())
+        })
+        .max_by_key(|e| e.metadata().and_then(|m| m.modified()).ok())
+        .map(|e| e.path())
+}
+
+/// The end of the newest log file, read again only once that file grows or is modified.
+#[derive(Debug, Default)]
+pub struct LogTail {
+    source: Option<(PathBuf, u64, Option<SystemTime>)>,
+    lines: Vec<(Option<Level>, String)>,
+}
+
+impl LogTail {
+    /// Reads the log again if it changed since the last call, returning whether it did.
+    pub fn refresh(&mut self) -> bool {
+        let source = get_latest_log_file_path().map(|path| {
+            let metadata = std::fs::metadata(&path).ok();
+            let len = metadata.as_ref().map_or(0, |m| m.len());
+            let modified = metadata.and_then(|m| m.modified().ok());
+
+            (path, len, modified)
+        });
+
+        if self.source.is_some() && self.source == source {
+            return false;
+        }
+
+        self.lines = match &source {
+            Some((path, _, _)) => read_log_tail(path),
+            None => Vec::new(),
+        };
+        self.source = source;
+
+        true
+    }
+
+    /// Each line paired with the level of the event it belongs to.
+    pub fn get_lines(&self) -> &[(Option<Level>, String)] {
+        &self.lines
+    }
+}
+
+fn read_log_tail(file_path: &Path) -> Vec<(Option<Level>, String)> {
+    let mut bytes = Vec::new();
+    let res = File::open(file_path).and_then(|mut file| {
+        let start = file.metadata()?.len().saturating_sub(LOG_TAIL_BYTES);
+        file.seek(SeekFrom::Start(start))?;
+        file.read_to_end(&mut bytes)?;
+
+        Ok(start)
+    });
+
+    let start = match res {
+        Ok(start) => start,
+        Err(e) => {
+            return vec![(
+                Some(Level::ERROR),
+                format!("Could not read {:?}: {}", file_path, e),
+            )]
         }
     };
 
-    let file_path = dir.join(LOG_FILE.clone());
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines = text.lines();
+    if start > 0 {
+        // the first line was cut by the seek
+        lines.next();
+    }
+
+    let mut level = None;
+    lines
+        .map(|line| {
+            // continuation lines belong to the event above them
+            let mut words = line.split_whitespace();
+            if words.next().is_some_and(|w| TIMESTAMP.is_match(w)) {
+                if let Some(parsed) = words.next().and_then(|l| l.parse().ok()) {
+                    level = Some(parsed);
+                }
+            }
 
-    (dir, file_path)
+            (level, line.to_string())
+        })
+        .collect()
 }
@@ -1,10 +1,20 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::config::{CustomCommand, Layout, Opener};
 use crate::event::Event;
+use crate::finder::Finder;
 use crate::fs::*;
+use crate::history::Location;
+use crate::jobs::Operation;
+use crate::open;
+use crate::search::Search;
+use crate::shell::{self, Selection};
+use crate::status::Level;
 use crate::App;
+use crate::ForegroundCommand;
 use crate::Input;
 use crate::Mode;
+use crate::Tab;
 
 use super::in_reexecution_allow_list;
 
@@ -27,6 +37,62 @@ pub fn handle_event(event: &Event, app: &mut App) {
         Event::ChangeToCreating => change_to_creating_mode(app),
         Event::ConfirmCreation => create_items(app),
         Event::CancelCreation => cancel_creation(app),
+        Event::ToggleOpenWith => toggle_open_with(app),
+        Event::ConfirmOpenWith => confirm_open_with(app),
+        Event::ChangeToCommandLine => change_to_command_line_mode(app, ""),
+        Event::ChangeToShellCommand => change_to_command_line_mode(app, "!"),
+        Event::ConfirmCommandLine => run_command_line(app),
+        Event::CancelCommandLine => cancel_command_line(app),
+        Event::ToggleOutput => toggle_output(app),
+        Event::RunCustomCommand(command) => run_custom_command(app, command),
+        Event::AcceptConfirmation => accept_confirmation(app),
+        Event::RejectConfirmation => reject_confirmation(app),
+        Event::OpenShell => open_shell(app),
+        Event::ChangeToSettingBookmark => change_to_setting_bookmark_mode(app),
+        Event::SetBookmark(key) => set_bookmark(app, *key),
+        Event::CancelBookmark => cancel_bookmark(app),
+        Event::ToggleBookmarks => toggle_show_bookmarks(app),
+        Event::JumpToBookmark(key) => jump_to_bookmark(app, *key),
+        Event::ConfirmBookmark => confirm_bookmark(app),
+        Event::DeleteBookmark => delete_bookmark(app),
+        Event::MoveBack => move_back(app),
+        Event::MoveForward => move_forward(app),
+        Event::ChangeToGoingTo => change_to_going_to_mode(app),
+        Event::ConfirmGoTo => confirm_go_to(app),
+        Event::CancelGoTo => cancel_go_to(app),
+        Event::CompletePath => complete_input_path(app),
+        Event::ChangeToJumping => change_to_jumping_mode(app),
+        Event::ConfirmJump => confirm_jump(app),
+        Event::CancelJump => cancel_jump(app),
+        Event::ChangeToFiltering => change_to_filtering_mode(app),
+        Event::ConfirmFilter => confirm_filter(app),
+        Event::ClearFilter => clear_filter(app),
+        Event::ChangeToFinding => change_to_finding_mode(app),
+        Event::ConfirmFind => confirm_find(app),
+        Event::CancelFind => cancel_find(app),
+        Event::ChangeToSearching => change_to_searching_mode(app),
+        Event::ToggleRegex => toggle_regex(app),
+        Event::ConfirmSearch => confirm_search(app),
+        Event::CancelSearch => cancel_search(app),
+        Event::OpenResult => open_result(app),
+        Event::CloseResults => close_results(app),
+        Event::NewTab => new_tab(app),
+        Event::CloseTab => close_tab(app),
+        Event::PreviousTab => previous_tab(app),
+        Event::NextTab => next_tab(app),
+        Event::CycleSort => cycle_sort(app),
+        Event::MoveQueue => move_queued_items(app),
+        Event::CopyQueue => copy_queued_items(app),
+        Event::CycleLayout => cycle_layout(app),
+        Event::SwitchPane => switch_pane(app),
+        Event::ToggleTree => toggle_tree(app),
+        Event::ToggleExpand => toggle_expand(app),
+        Event::Refresh => app.listings.clear(),
+        Event::ToggleJobs => toggle_show_jobs(app),
+        Event::CancelJob => cancel_job(app),
+        Event::RetryJob => retry_job(app),
+        Event::ToggleLog => toggle_log(app),
+        Event::CycleLogLevel => cycle_log_level(app),
         Event::Noop => {}
     }
 }
@@ -35,45 +101,184 @@ fn execute_command(app: &mut App) {
     if let Some(index) = app.command_list.state.selected() {
         let second_hand_event = app.command_list.items[index].clone();
 
+        toggle_show_commands(app);
+
         if in_reexecution_allow_list(&second_hand_event) {
             handle_event(&second_hand_event, app);
         }
-
-        toggle_show_commands(app);
     }
 }
 
 fn create_items(app: &mut App) {
     app.mode = Mode::Normal;
 
-    create_path(&app.input.text);
+    let path = app.tab().path.join(&app.input.text);
+    app.jobs.spawn(Operation::Create, vec![path]);
 
     clear(&mut app.input);
 }
 
 fn move_to_child(app: &mut App) {
-    if let Some(index) = app.left_rect_list.state.selected() {
-        let new_path = &app.left_rect_list.items[index].path();
+    if let Some(index) = app.tab().list.state.selected() {
+        let new_path = &app.tab().list.items[index].clone();
         if !new_path.is_dir() {
+            open_file(app, new_path);
             return;
         }
 
-        if let Err(_r) = change_dir(new_path, || app.left_rect_list.state.select_first()) {
-            tracing::error!("Could not move to child dir {:?}", new_path);
+        if let Err(e) = navigate(app, new_path, None) {
+            app.notify(
+                Level::Error,
+                format!("Could not move to {:?}: {:#}", new_path, e),
+            );
+        }
+    }
+}
+
+fn open_file(app: &mut App, path: &Path) {
+    let openers = open::get_openers(&app.config, path);
+
+    if let Some(opener) = openers.first() {
+        open_with(app, opener, path);
+    }
+}
+
+fn open_with(app: &mut App, opener: &Opener, path: &Path) {
+    let mut command = match open::get_command(opener, path) {
+        Ok(command) => command,
+        Err(e) => {
+            app.notify(Level::Error, format!("Could not open {:?}: {:#}", path, e));
+            return;
+        }
+    };
+    command.current_dir(&app.tab().path);
+
+    if opener.detached {
+        let program = command.get_program().to_string_lossy().into_owned();
+        if let Err(e) = open::spawn_detached(command) {
+            app.notify(Level::Error, format!("Could not run {}: {}", program, e));
+        }
+    } else {
+        app.foreground_command = Some(ForegroundCommand {
+            command,
+            wait_for_key: false,
+        });
+    }
+}
+
+fn toggle_open_with(app: &mut App) {
+    if app.mode == Mode::OpeningWith {
+        app.mode = Mode::Normal;
+        return;
+    }
+
+    if let Some(index) = app.tab().list.state.selected() {
+        let path = app.tab().list.items[index].clone();
+        if path.is_dir() {
+            return;
+        }
+
+        app.opener_list.items = open::get_openers(&app.config, &path);
+        app.opener_list.path = path;
+        app.opener_list.state.select_first();
+        app.mode = Mode::OpeningWith;
+    }
+}
+
+fn confirm_open_with(app: &mut App) {
+    app.mode = Mode::Normal;
+
+    if let Some(index) = app.opener_list.state.selected() {
+        let opener = app.opener_list.items[index].clone();
+        let path = app.opener_list.path.clone();
+
+        open_with(app, &opener, &path);
+    }
+}
+
+fn get_location(app: &App) -> Location {
+    Location {
+        path: app.tab().path.clone(),
+        selected: app.tab().list.get_selected_path(),
+    }
+}
+
+fn go_to(app: &mut App, path: &Path, selected: Option<PathBuf>) -> color_eyre::Result<()> {
+    let location = get_location(app);
+    if let Some(selected) = location.selected {
+        app.cursor_memory.insert(location.path, selected);
+    }
+
+    let path = resolve_dir(path)?;
+
+    // entries are listed under the resolved path, so the selection has to be too
+    let selected = selected.map(|s| match s.file_name() {
+        Some(name) if !s.starts_with(&path) => path.join(name),
+        _ => s,
+    });
+
+    app.listings.invalidate(&path);
+
+    let tab = app.tab_mut();
+    tab.path = path.clone();
+    tab.list.state.select_first();
+    tab.list.pending_selection = selected;
+    tab.filter.clear();
+    app.frecency.visit(&path);
+
+    Ok(())
+}
+
+/// Changes to `path`, recording the current location in the history.
+fn navigate(app: &mut App, path: &Path, selected: Option<PathBuf>) -> color_eyre::Result<()> {
+    let location = get_location(app);
+
+    go_to(app, path, selected)?;
+    app.tab_mut().history.push(location);
+
+    Ok(())
+}
+
+fn move_back(app: &mut App) {
+    let current = get_location(app);
+
+    if let Some(previous) = app.tab_mut().history.back(current) {
+        if let Err(e) = go_to(app, &previous.path, previous.selected) {
+            app.notify(
+                Level::Error,
+                format!("Could not move back to {:?}: {:#}", previous.path, e),
+            );
+        }
+    }
+}
+
+fn move_forward(app: &mut App) {
+    let current = get_location(app);
+
+    if let Some(next) = app.tab_mut().history.forward(current) {
+        if let Err(e) = go_to(app, &next.path, next.selected) {
+            app.notify(
+                Level::Error,
+                format!("Could not move forward to {:?}: {:#}", next.path, e),
+            );
         }
     }
 }
 
 fn move_to_parent(app: &mut App) {
-    let parent = current_dir().unwrap().parent().map(|p| p.to_path_buf());
+    let current = app.tab().path.clone();
+    let parent = current.parent().map(|p| p.to_path_buf());
 
     if parent.is_none() {
         return;
     }
 
     let parent = &parent.unwrap();
-    if let Err(_r) = change_dir(parent, || app.left_rect_list.state.select_first()) {
-        tracing::error!("Could not move to {:?}: {}", parent, _r);
+    if let Err(e) = navigate(app, parent, Some(current.clone())) {
+        app.notify(
+            Level::Error,
+            format!("Could not move to {:?}: {:#}", parent, e),
+        );
     }
 }
 
@@ -82,8 +287,8 @@ fn toggle_show_hidden(app: &mut App) {
 }
 
 fn toggle_presence_on_queue(app: &mut App) {
-    if let Some(index) = app.left_rect_list.state.selected() {
-        let item = app.left_rect_list.items[index].path();
+    if let Some(index) = app.tab().list.state.selected() {
+        let item = app.tab().list.items[index].clone();
         if app.queued_items.contains(&item) {
             app.queued_items.remove(&item);
         } else {
@@ -94,25 +299,598 @@ fn toggle_presence_on_queue(app: &mut App) {
 
 fn delete_queued_items(app: &mut App) {
     let mut items_to_delete: Vec<PathBuf> = app.queued_items.iter().cloned().collect();
+    if items_to_delete.is_empty() {
+        app.notify(Level::Warning, "Nothing queued to delete".to_string());
+        return;
+    }
 
     items_to_delete.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
 
-    let current_dir = &current_dir().unwrap();
+    let current_dir = &app.tab().path.clone();
     if items_to_delete.contains(current_dir) {
         if let Some(parent) = current_dir.parent() {
-            if let Err(e) = change_dir(parent, || app.left_rect_list.state.select_first()) {
-                tracing::error!("Error while moving to parent of {:?}: {}", current_dir, e);
+            if let Err(e) = navigate(app, parent, None) {
+                app.notify(
+                    Level::Error,
+                    format!("Error while moving to parent of {:?}: {:#}", current_dir, e),
+                );
                 return;
             }
         }
     }
 
-    delete_all(items_to_delete);
+    app.jobs.spawn(Operation::Delete, items_to_delete);
+}
+
+/// The other pane's directory in the dual layout, the current one otherwise.
+fn get_queue_destination(app: &App) -> PathBuf {
+    if app.layout == Layout::Dual && app.other_tab != app.active_tab {
+        app.tabs[app.other_tab].path.clone()
+    } else {
+        app.tab().path.clone()
+    }
+}
+
+fn get_queued_items(app: &App) -> Vec<PathBuf> {
+    let mut items: Vec<PathBuf> = app.queued_items.iter().cloned().collect();
+    items.sort();
+
+    items
+}
+
+fn move_queued_items(app: &mut App) {
+    let dest = get_queue_destination(app);
+
+    let items = get_queued_items(app);
+    if items.is_empty() {
+        app.notify(Level::Warning, "Nothing queued to move".to_string());
+        return;
+    }
+
+    app.jobs.spawn(Operation::Move(dest), items);
+}
+
+fn copy_queued_items(app: &mut App) {
+    let dest = get_queue_destination(app);
+
+    let items = get_queued_items(app);
+    if items.is_empty() {
+        app.notify(Level::Warning, "Nothing queued to copy".to_string());
+        return;
+    }
+
+    app.jobs.spawn(Operation::Copy(dest), items);
+}
+
+fn fix_other_tab(app: &mut App) {
+    if app.other_tab >= app.tabs.len() || app.other_tab == app.active_tab {
+        app.other_tab = (app.active_tab + 1) % app.tabs.len();
+    }
+}
+
+fn cycle_layout(app: &mut App) {
+    app.layout = app.layout.next();
+
+    if app.layout == Layout::Dual && app.tabs.len() == 1 {
+        app.tabs.push(Tab::new(app.tab().path.clone()));
+    }
+    fix_other_tab(app);
+}
+
+fn switch_pane(app: &mut App) {
+    if app.layout == Layout::Dual && app.other_tab != app.active_tab {
+        switch_tab(app, app.other_tab);
+    }
+}
+
+fn switch_tab(app: &mut App, index: usize) {
+    let previous = app.active_tab;
+
+    app.active_tab = index;
+    if app.other_tab == index {
+        app.other_tab = previous;
+    }
+}
+
+fn new_tab(app: &mut App) {
+    app.tabs
+        .insert(app.active_tab + 1, Tab::new(app.tab().path.clone()));
+    if app.other_tab > app.active_tab {
+        app.other_tab += 1;
+    }
+
+    switch_tab(app, app.active_tab + 1);
+}
+
+fn close_tab(app: &mut App) {
+    if app.tabs.len() == 1 {
+        return;
+    }
+
+    // both panes of the dual layout need a tab of their own
+    if app.layout == Layout::Dual && app.tabs.len() == 2 {
+        app.notify(
+            Level::Warning,
+            "Cannot close one of the two panes".to_string(),
+        );
+        return;
+    }
+
+    app.tabs.remove(app.active_tab);
+    if app.other_tab > app.active_tab {
+        app.other_tab -= 1;
+    }
+    app.active_tab = app.active_tab.min(app.tabs.len() - 1);
+    fix_other_tab(app);
+}
+
+fn previous_tab(app: &mut App) {
+    let index = (app.active_tab + app.tabs.len() - 1) % app.tabs.len();
+    switch_tab(app, index);
+}
+
+fn next_tab(app: &mut App) {
+    let index = (app.active_tab + 1) % app.tabs.len();
+    switch_tab(app, index);
+}
+
+fn toggle_tree(app: &mut App) {
+    let tab = app.tab_mut();
+    tab.tree = !tab.tree;
+}
+
+fn toggle_expand(app: &mut App) {
+    let tab = app.tab_mut();
+    if !tab.tree {
+        return;
+    }
+
+    let Some(path) = tab
+        .list
+        .state
+        .selected()
+        .and_then(|index| tab.list.items.get(index))
+        .cloned()
+    else {
+        return;
+    };
+
+    if !path.is_dir() {
+        return;
+    }
+
+    if !tab.expanded.remove(&path) {
+        tab.expanded.insert(path);
+    }
+}
+
+fn cycle_sort(app: &mut App) {
+    let tab = app.tab_mut();
+    tab.sort = tab.sort.next();
 }
 
 fn change_to_creating_mode(app: &mut App) {
     app.mode = Mode::Creating;
-    app.input = Input::new(current_dir().unwrap().display().to_string())
+    app.input = Input::new(app.tab().path.display().to_string())
+}
+
+fn change_to_command_line_mode(app: &mut App, prefix: &str) {
+    app.mode = Mode::CommandLine;
+    app.input = Input::new(prefix.to_string());
+}
+
+fn get_selection(app: &App) -> Selection {
+    let list = &app.tab().list;
+    let file = list
+        .state
+        .selected()
+        .and_then(|index| list.items.get(index))
+        .cloned();
+
+    let mut queued: Vec<PathBuf> = app.queued_items.iter().cloned().collect();
+    queued.sort();
+
+    Selection {
+        file,
+        queued,
+        dir: app.tab().path.clone(),
+    }
+}
+
+fn run_command_line(app: &mut App) {
+    app.mode = Mode::Normal;
+
+    let text = app.input.text.trim().to_string();
+    clear(&mut app.input);
+
+    if text == "cd" || text.starts_with("cd ") {
+        go_to_path(app, text[2..].trim());
+        return;
+    }
+
+    let (interactive, template) = match text.strip_prefix('!') {
+        Some(rest) => (true, rest.trim()),
+        None => (false, text.as_str()),
+    };
+
+    run_template(app, template, template, interactive);
+}
+
+fn run_template(app: &mut App, title: &str, template: &str, interactive: bool) {
+    if template.is_empty() {
+        return;
+    }
+
+    let selection = get_selection(app);
+    let script = shell::expand(template, &selection);
+
+    if interactive {
+        app.foreground_command = Some(ForegroundCommand {
+            command: shell::get_command(&script, &selection.dir),
+            wait_for_key: true,
+        });
+        return;
+    }
+
+    match shell::Execution::new(&script, &selection.dir) {
+        Ok(execution) => {
+            app.output.title = title.to_string();
+            app.output.lines = Vec::new();
+            app.output.scroll = 0;
+            app.output.execution = Some(execution);
+            app.mode = Mode::ShowingOutput;
+        }
+        Err(e) => app.notify(Level::Error, format!("Could not run {:?}: {}", script, e)),
+    }
+}
+
+fn cancel_command_line(app: &mut App) {
+    app.mode = Mode::Normal;
+    clear(&mut app.input);
+}
+
+fn change_to_going_to_mode(app: &mut App) {
+    app.mode = Mode::GoingTo;
+
+    let mut text = app.tab().path.display().to_string();
+    if !text.ends_with(get_delimiter()) {
+        text.push_str(get_delimiter());
+    }
+    app.input = Input::new(text);
+}
+
+fn go_to_path(app: &mut App, text: &str) {
+    let path = if text.is_empty() {
+        match get_home_dir() {
+            Some(home) => home,
+            None => return,
+        }
+    } else {
+        expand_path(text, &app.tab().path)
+    };
+
+    let res = if path.is_dir() {
+        navigate(app, &path, None)
+    } else {
+        match path.parent() {
+            Some(parent) if path.exists() => navigate(app, parent, Some(path.clone())),
+            _ => Err(color_eyre::eyre::eyre!("No such directory")),
+        }
+    };
+
+    if let Err(e) = res {
+        app.notify(Level::Error, format!("Could not go to {:?}: {:#}", path, e));
+    }
+}
+
+fn confirm_go_to(app: &mut App) {
+    app.mode = Mode::Normal;
+
+    let text = app.input.text.trim().to_string();
+    clear(&mut app.input);
+
+    go_to_path(app, &text);
+}
+
+fn cancel_go_to(app: &mut App) {
+    app.mode = Mode::Normal;
+    clear(&mut app.input);
+}
+
+fn complete_input_path(app: &mut App) {
+    let start = if app.mode == Mode::CommandLine {
+        app.input.text.rfind(' ').map(|i| i + 1).unwrap_or(0)
+    } else {
+        0
+    };
+    let (head, tail) = app.input.text.split_at(start);
+
+    if let Some(completed) = complete_path(tail, &app.tab().path) {
+        app.input = Input::new(format!("{}{}", head, completed));
+    }
+}
+
+fn change_to_jumping_mode(app: &mut App) {
+    app.mode = Mode::Jumping;
+    app.input = Input::default();
+    app.jump_list.state.select_first();
+}
+
+fn confirm_jump(app: &mut App) {
+    app.mode = Mode::Normal;
+    clear(&mut app.input);
+
+    if let Some(path) = app
+        .jump_list
+        .state
+        .selected()
+        .and_then(|index| app.jump_list.items.get(index))
+        .cloned()
+    {
+        if let Err(e) = navigate(app, &path, None) {
+            app.notify(
+                Level::Error,
+                format!("Could not jump to {:?}: {:#}", path, e),
+            );
+        }
+    }
+}
+
+fn cancel_jump(app: &mut App) {
+    app.mode = Mode::Normal;
+    clear(&mut app.input);
+}
+
+fn change_to_filtering_mode(app: &mut App) {
+    app.mode = Mode::Filtering;
+    app.input = Input::new(app.tab().filter.clone());
+}
+
+fn confirm_filter(app: &mut App) {
+    app.mode = Mode::Normal;
+    app.tab_mut().filter = app.input.text.clone();
+    clear(&mut app.input);
+}
+
+fn clear_filter(app: &mut App) {
+    app.mode = Mode::Normal;
+    app.tab_mut().filter.clear();
+    clear(&mut app.input);
+}
+
+fn change_to_finding_mode(app: &mut App) {
+    app.mode = Mode::Finding;
+    app.input = Input::default();
+    app.finder = Some(Finder::new(&app.tab().path, app.show_hidden));
+    app.finder_list.select_first();
+}
+
+fn confirm_find(app: &mut App) {
+    app.mode = Mode::Normal;
+    clear(&mut app.input);
+
+    let path = app
+        .finder
+        .take()
+        .zip(app.finder_list.selected())
+        .and_then(|(finder, index)| finder.get_path(index));
+
+    if let Some(path) = path {
+        if let Some(parent) = path.parent() {
+            if let Err(e) = navigate(app, parent, Some(path.clone())) {
+                app.notify(Level::Error, format!("Could not go to {:?}: {:#}", path, e));
+            }
+        }
+    }
+}
+
+fn cancel_find(app: &mut App) {
+    app.mode = Mode::Normal;
+    app.finder = None;
+    clear(&mut app.input);
+}
+
+fn change_to_searching_mode(app: &mut App) {
+    app.mode = Mode::Searching;
+    app.input = Input::default();
+}
+
+fn toggle_regex(app: &mut App) {
+    app.search_regex = !app.search_regex;
+}
+
+fn confirm_search(app: &mut App) {
+    let pattern = app.input.text.clone();
+    if pattern.is_empty() {
+        return;
+    }
+
+    match Search::new(&app.tab().path, app.show_hidden, &pattern, app.search_regex) {
+        Ok(search) => {
+            app.search = Some(search);
+            app.search_list.select_first();
+            app.mode = Mode::ShowingResults;
+            clear(&mut app.input);
+        }
+        Err(e) => app.notify(
+            Level::Error,
+            format!("Invalid search pattern {:?}: {}", pattern, e),
+        ),
+    }
+}
+
+fn cancel_search(app: &mut App) {
+    app.mode = Mode::Normal;
+    clear(&mut app.input);
+}
+
+fn open_result(app: &mut App) {
+    let Some(search) = app.search.as_ref() else {
+        return;
+    };
+    let Some(found) = app
+        .search_list
+        .selected()
+        .and_then(|index| search.get_matches().get(index))
+    else {
+        return;
+    };
+
+    let path = search.get_path(found);
+    match open::get_editor_command(&path, found.line) {
+        Some(mut command) => {
+            command.current_dir(&app.tab().path);
+            app.foreground_command = Some(ForegroundCommand {
+                command,
+                wait_for_key: false,
+            })
+        }
+        None => open_file(app, &path),
+    }
+}
+
+fn close_results(app: &mut App) {
+    app.mode = Mode::Normal;
+    app.search = None;
+}
+
+fn toggle_output(app: &mut App) {
+    app.mode = Mode::Normal;
+
+    if let Some(mut execution) = app.output.execution.take() {
+        execution.kill();
+    }
+}
+
+fn toggle_log(app: &mut App) {
+    if app.mode != Mode::ShowingLog {
+        app.mode = Mode::ShowingLog;
+        app.log_viewer.scroll_back = 0;
+    } else {
+        app.mode = Mode::Normal;
+    }
+}
+
+fn cycle_log_level(app: &mut App) {
+    app.log_viewer.level = match app.log_viewer.level {
+        None => Some(tracing::Level::INFO),
+        Some(tracing::Level::INFO) => Some(tracing::Level::WARN),
+        Some(tracing::Level::WARN) => Some(tracing::Level::ERROR),
+        Some(_) => None,
+    };
+    app.log_viewer.scroll_back = 0;
+}
+
+fn open_shell(app: &mut App) {
+    app.foreground_command = Some(ForegroundCommand {
+        command: shell::get_subshell(&app.tab().path),
+        wait_for_key: false,
+    });
+}
+
+fn change_to_setting_bookmark_mode(app: &mut App) {
+    app.mode = Mode::SettingBookmark;
+}
+
+fn set_bookmark(app: &mut App, key: char) {
+    app.mode = Mode::Normal;
+    let path = app.tab().path.clone();
+    app.bookmarks.set(key, path);
+}
+
+fn cancel_bookmark(app: &mut App) {
+    app.mode = Mode::Normal;
+}
+
+fn toggle_show_bookmarks(app: &mut App) {
+    if app.mode != Mode::ShowingBookmarks {
+        app.mode = Mode::ShowingBookmarks;
+        app.bookmark_list.select_first();
+    } else {
+        app.mode = Mode::Normal;
+    }
+}
+
+fn jump_to_bookmark(app: &mut App, key: char) {
+    let Some(path) = app.bookmarks.get(key).cloned() else {
+        return;
+    };
+
+    app.mode = Mode::Normal;
+    if let Err(e) = navigate(app, &path, None) {
+        app.notify(
+            Level::Error,
+            format!("Could not jump to bookmark {:?}: {:#}", path, e),
+        );
+    }
+}
+
+fn confirm_bookmark(app: &mut App) {
+    if let Some(key) = app
+        .bookmark_list
+        .selected()
+        .and_then(|index| app.bookmarks.get_key(index))
+    {
+        jump_to_bookmark(app, key);
+    }
+}
+
+fn delete_bookmark(app: &mut App) {
+    if let Some(key) = app
+        .bookmark_list
+        .selected()
+        .and_then(|index| app.bookmarks.get_key(index))
+    {
+        app.bookmarks.remove(key);
+    }
+}
+
+fn run_custom_command(app: &mut App, command: &CustomCommand) {
+    if command.confirm && app.mode != Mode::Confirming {
+        app.pending_confirmation = Some(Event::RunCustomCommand(command.clone()));
+        app.mode = Mode::Confirming;
+        return;
+    }
+
+    app.mode = Mode::Normal;
+    run_template(app, &command.name, &command.command, command.interactive);
+}
+
+fn accept_confirmation(app: &mut App) {
+    if let Some(event) = app.pending_confirmation.take() {
+        handle_event(&event, app);
+    }
+
+    if app.mode == Mode::Confirming {
+        app.mode = Mode::Normal;
+    }
+}
+
+fn reject_confirmation(app: &mut App) {
+    app.pending_confirmation = None;
+    app.mode = Mode::Normal;
+}
+
+fn toggle_show_jobs(app: &mut App) {
+    if app.mode != Mode::ShowingJobs {
+        app.mode = Mode::ShowingJobs;
+        app.job_list.select_last();
+    } else {
+        app.mode = Mode::Normal;
+    }
+}
+
+fn cancel_job(app: &mut App) {
+    if let Some(index) = app.job_list.selected() {
+        app.jobs.cancel(index);
+    }
+}
+
+fn retry_job(app: &mut App) {
+    if let Some(index) = app.job_list.selected() {
+        app.jobs.retry(index);
+        app.job_list.select_last();
+    }
 }
 
 fn toggle_show_commands(app: &mut App) {
@@ -125,18 +903,50 @@ fn toggle_show_commands(app: &mut App) {
 }
 
 fn move_down(app: &mut App) {
-    if app.mode == Mode::Normal {
-        app.left_rect_list.state.select_next()
+    if app.mode == Mode::Normal || app.mode == Mode::Filtering {
+        app.tab_mut().list.state.select_next()
     } else if app.mode == Mode::ShowingCommands {
         app.command_list.state.select_next()
+    } else if app.mode == Mode::OpeningWith {
+        app.opener_list.state.select_next()
+    } else if app.mode == Mode::ShowingOutput {
+        app.output.scroll = app.output.scroll.saturating_add(1)
+    } else if app.mode == Mode::ShowingBookmarks {
+        app.bookmark_list.select_next()
+    } else if app.mode == Mode::Jumping {
+        app.jump_list.state.select_next()
+    } else if app.mode == Mode::Finding {
+        app.finder_list.select_next()
+    } else if app.mode == Mode::ShowingResults {
+        app.search_list.select_next()
+    } else if app.mode == Mode::ShowingJobs {
+        app.job_list.select_next()
+    } else if app.mode == Mode::ShowingLog {
+        app.log_viewer.scroll_back = app.log_viewer.scroll_back.saturating_sub(1)
     }
 }
 
 fn move_up(app: &mut App) {
-    if app.mode == Mode::Normal {
-        app.left_rect_list.state.select_previous()
+    if app.mode == Mode::Normal || app.mode == Mode::Filtering {
+        app.tab_mut().list.state.select_previous()
     } else if app.mode == Mode::ShowingCommands {
         app.command_list.state.select_previous()
+    } else if app.mode == Mode::OpeningWith {
+        app.opener_list.state.select_previous()
+    } else if app.mode == Mode::ShowingOutput {
+        app.output.scroll = app.output.scroll.saturating_sub(1)
+    } else if app.mode == Mode::ShowingBookmarks {
+        app.bookmark_list.select_previous()
+    } else if app.mode == Mode::Jumping {
+        app.jump_list.state.select_previous()
+    } else if app.mode == Mode::Finding {
+        app.finder_list.select_previous()
+    } else if app.mode == Mode::ShowingResults {
+        app.search_list.select_previous()
+    } else if app.mode == Mode::ShowingJobs {
+        app.job_list.select_previous()
+    } else if app.mode == Mode::ShowingLog {
+        app.log_viewer.scroll_back = app.log_viewer.scroll_back.saturating_add(1)
     }
 }
 
@@ -145,13 +955,13 @@ fn quit(app: &mut App) {
 }
 
 fn delete_char(input: &mut Input) {
-    let idx = byte_index(input).saturating_sub(1);
-
     if input.char_index == 0 {
         return;
     }
 
-    input.text.remove(idx);
+    if let Some((idx, _)) = input.text.char_indices().nth(input.char_index - 1) {
+        input.text.remove(idx);
+    }
     move_to_left(input);
 }
 
@@ -198,3 +1008,34 @@ fn byte_index(input: &Input) -> usize {
 fn cancel_creation(app: &mut App) {
     app.mode = Mode::Normal;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deletes_multi_byte_chars() {
+        let mut input = Input::new("/tmp/café".to_string());
+
+        delete_char(&mut input);
+        assert_eq!(input.text, "/tmp/caf");
+        assert_eq!(input.char_index, 8);
+
+        add_char(&mut input, "é");
+        add_char(&mut input, "s");
+        move_to_left(&mut input);
+        delete_char(&mut input);
+        assert_eq!(input.text, "/tmp/cafs");
+        assert_eq!(input.char_index, 8);
+    }
+
+    #[test]
+    fn deletes_nothing_at_the_start() {
+        let mut input = Input::new("é".to_string());
+        input.char_index = 0;
+
+        delete_char(&mut input);
+        assert_eq!(input.text, "é");
+        assert_eq!(input.char_index, 0);
+    }
+}
@@ -1,5 +1,6 @@
 use ratatui::crossterm::event::KeyCode;
 
+use crate::config::{Config, CustomCommand};
 use crate::Mode;
 pub use handler::handle_event;
 use Event::*;
@@ -26,21 +27,112 @@ pub enum Event {
     ConfirmCreation,
     ExecuteCommand,
     CancelCreation,
+    ToggleOpenWith,
+    ConfirmOpenWith,
+    ChangeToCommandLine,
+    ChangeToShellCommand,
+    ConfirmCommandLine,
+    CancelCommandLine,
+    ToggleOutput,
+    RunCustomCommand(CustomCommand),
+    AcceptConfirmation,
+    RejectConfirmation,
+    OpenShell,
+    ChangeToSettingBookmark,
+    SetBookmark(char),
+    CancelBookmark,
+    ToggleBookmarks,
+    JumpToBookmark(char),
+    ConfirmBookmark,
+    DeleteBookmark,
+    MoveBack,
+    MoveForward,
+    ChangeToGoingTo,
+    ConfirmGoTo,
+    CancelGoTo,
+    CompletePath,
+    ChangeToJumping,
+    ConfirmJump,
+    CancelJump,
+    ChangeToFiltering,
+    ConfirmFilter,
+    ClearFilter,
+    ChangeToFinding,
+    ConfirmFind,
+    CancelFind,
+    ChangeToSearching,
+    ToggleRegex,
+    ConfirmSearch,
+    CancelSearch,
+    OpenResult,
+    CloseResults,
+    NewTab,
+    CloseTab,
+    PreviousTab,
+    NextTab,
+    CycleSort,
+    MoveQueue,
+    CopyQueue,
+    CycleLayout,
+    SwitchPane,
+    ToggleTree,
+    ToggleExpand,
+    Refresh,
+    ToggleJobs,
+    CancelJob,
+    RetryJob,
+    ToggleLog,
+    CycleLogLevel,
 }
 
-pub fn get_event<'a>(mode: &'a Mode, code: &'a KeyCode) -> Event {
+pub fn get_event<'a>(mode: &'a Mode, code: &'a KeyCode, config: &'a Config) -> Event {
+    // configured keys take precedence over the built-in ones
+    if let (Mode::Normal, KeyCode::Char(c)) = (mode, code) {
+        if let Some(command) = get_custom_command(config, *c) {
+            return RunCustomCommand(command.clone());
+        }
+    }
+
     match mode {
         Mode::Normal => match code {
             KeyCode::Char('q') => Quit,
             KeyCode::Up | KeyCode::Char('j') => MoveUp,
             KeyCode::Down | KeyCode::Char('k') => MoveDown,
             KeyCode::Left | KeyCode::Char('h') => MoveToParent,
-            KeyCode::Right | KeyCode::Char('l') => MoveToChild,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => MoveToChild,
             KeyCode::Char('.') => ToggleHidden,
             KeyCode::Char(' ') => ToggleQueue,
             KeyCode::Char('d') => DeleteQueue,
             KeyCode::Char('n') => ChangeToCreating,
             KeyCode::Char('?') => ToggleCommands,
+            KeyCode::Char('o') => ToggleOpenWith,
+            KeyCode::Char(':') => ChangeToCommandLine,
+            KeyCode::Char('!') => ChangeToShellCommand,
+            KeyCode::Char('S') => OpenShell,
+            KeyCode::Char('m') => ChangeToSettingBookmark,
+            KeyCode::Char('\'') => ToggleBookmarks,
+            KeyCode::Char('H') => MoveBack,
+            KeyCode::Char('L') => MoveForward,
+            KeyCode::Char('g') => ChangeToGoingTo,
+            KeyCode::Char('z') => ChangeToJumping,
+            KeyCode::Char('/') => ChangeToFiltering,
+            KeyCode::Char('f') => ChangeToFinding,
+            KeyCode::Char('F') => ChangeToSearching,
+            KeyCode::Char('t') => NewTab,
+            KeyCode::Char('x') => CloseTab,
+            KeyCode::Char('[') => PreviousTab,
+            KeyCode::Char(']') => NextTab,
+            KeyCode::Char('s') => CycleSort,
+            KeyCode::Char('p') => MoveQueue,
+            KeyCode::Char('c') => CopyQueue,
+            KeyCode::Char('v') => CycleLayout,
+            KeyCode::Tab => SwitchPane,
+            KeyCode::Char('T') => ToggleTree,
+            KeyCode::Char('e') => ToggleExpand,
+            KeyCode::Char('R') => Refresh,
+            KeyCode::Char('J') => ToggleJobs,
+            KeyCode::Char('E') => ToggleLog,
+            KeyCode::Esc => ClearFilter,
             _ => Noop,
         },
         Mode::ShowingCommands => match code {
@@ -53,16 +145,138 @@ pub fn get_event<'a>(mode: &'a Mode, code: &'a KeyCode) -> Event {
         Mode::Creating => match code {
             KeyCode::Esc => CancelCreation,
             KeyCode::Enter => ConfirmCreation,
+            KeyCode::Tab => CompletePath,
+            KeyCode::Char(c) => AddChar(c.to_string()),
+            KeyCode::Backspace => DeleteChar,
+            KeyCode::Left => MoveLeft,
+            KeyCode::Right => MoveRight,
+            _ => Noop,
+        },
+        Mode::OpeningWith => match code {
+            KeyCode::Esc | KeyCode::Char('q') => ToggleOpenWith,
+            KeyCode::Up | KeyCode::Char('j') => MoveUp,
+            KeyCode::Down | KeyCode::Char('k') => MoveDown,
+            KeyCode::Enter => ConfirmOpenWith,
+            _ => Noop,
+        },
+        Mode::CommandLine => match code {
+            KeyCode::Esc => CancelCommandLine,
+            KeyCode::Enter => ConfirmCommandLine,
+            KeyCode::Tab => CompletePath,
             KeyCode::Char(c) => AddChar(c.to_string()),
             KeyCode::Backspace => DeleteChar,
             KeyCode::Left => MoveLeft,
             KeyCode::Right => MoveRight,
             _ => Noop,
         },
+        Mode::ShowingOutput => match code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => ToggleOutput,
+            KeyCode::Up | KeyCode::Char('j') => MoveUp,
+            KeyCode::Down | KeyCode::Char('k') => MoveDown,
+            _ => Noop,
+        },
+        Mode::GoingTo => match code {
+            KeyCode::Esc => CancelGoTo,
+            KeyCode::Enter => ConfirmGoTo,
+            KeyCode::Tab => CompletePath,
+            KeyCode::Char(c) => AddChar(c.to_string()),
+            KeyCode::Backspace => DeleteChar,
+            KeyCode::Left => MoveLeft,
+            KeyCode::Right => MoveRight,
+            _ => Noop,
+        },
+        Mode::Jumping => match code {
+            KeyCode::Esc => CancelJump,
+            KeyCode::Enter => ConfirmJump,
+            KeyCode::Up => MoveUp,
+            KeyCode::Down => MoveDown,
+            KeyCode::Char(c) => AddChar(c.to_string()),
+            KeyCode::Backspace => DeleteChar,
+            KeyCode::Left => MoveLeft,
+            KeyCode::Right => MoveRight,
+            _ => Noop,
+        },
+        Mode::Filtering => match code {
+            KeyCode::Esc => ClearFilter,
+            KeyCode::Enter => ConfirmFilter,
+            KeyCode::Up => MoveUp,
+            KeyCode::Down => MoveDown,
+            KeyCode::Char(c) => AddChar(c.to_string()),
+            KeyCode::Backspace => DeleteChar,
+            KeyCode::Left => MoveLeft,
+            KeyCode::Right => MoveRight,
+            _ => Noop,
+        },
+        Mode::Finding => match code {
+            KeyCode::Esc => CancelFind,
+            KeyCode::Enter => ConfirmFind,
+            KeyCode::Up => MoveUp,
+            KeyCode::Down => MoveDown,
+            KeyCode::Char(c) => AddChar(c.to_string()),
+            KeyCode::Backspace => DeleteChar,
+            KeyCode::Left => MoveLeft,
+            KeyCode::Right => MoveRight,
+            _ => Noop,
+        },
+        Mode::Searching => match code {
+            KeyCode::Esc => CancelSearch,
+            KeyCode::Enter => ConfirmSearch,
+            KeyCode::Tab => ToggleRegex,
+            KeyCode::Char(c) => AddChar(c.to_string()),
+            KeyCode::Backspace => DeleteChar,
+            KeyCode::Left => MoveLeft,
+            KeyCode::Right => MoveRight,
+            _ => Noop,
+        },
+        Mode::ShowingResults => match code {
+            KeyCode::Esc | KeyCode::Char('q') => CloseResults,
+            KeyCode::Up | KeyCode::Char('j') => MoveUp,
+            KeyCode::Down | KeyCode::Char('k') => MoveDown,
+            KeyCode::Enter => OpenResult,
+            _ => Noop,
+        },
+        Mode::Confirming => match code {
+            KeyCode::Enter | KeyCode::Char('y') => AcceptConfirmation,
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => RejectConfirmation,
+            _ => Noop,
+        },
+        Mode::SettingBookmark => match code {
+            KeyCode::Esc => CancelBookmark,
+            KeyCode::Char(c) => SetBookmark(*c),
+            _ => Noop,
+        },
+        Mode::ShowingBookmarks => match code {
+            KeyCode::Esc | KeyCode::Char('\'') => ToggleBookmarks,
+            KeyCode::Up => MoveUp,
+            KeyCode::Down => MoveDown,
+            KeyCode::Enter => ConfirmBookmark,
+            KeyCode::Delete => DeleteBookmark,
+            KeyCode::Char(c) => JumpToBookmark(*c),
+            _ => Noop,
+        },
+        Mode::ShowingJobs => match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('J') => ToggleJobs,
+            KeyCode::Up | KeyCode::Char('j') => MoveUp,
+            KeyCode::Down | KeyCode::Char('k') => MoveDown,
+            KeyCode::Char('c') => CancelJob,
+            KeyCode::Char('r') => RetryJob,
+            _ => Noop,
+        },
+        Mode::ShowingLog => match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('E') => ToggleLog,
+            KeyCode::Up | KeyCode::Char('j') => MoveUp,
+            KeyCode::Down | KeyCode::Char('k') => MoveDown,
+            KeyCode::Char('l') => CycleLogLevel,
+            _ => Noop,
+        },
     }
 }
 
-fn get_events() -> [Event; 17] {
+fn get_custom_command(config: &Config, key: char) -> Option<&CustomCommand> {
+    config.commands.iter().find(|c| c.key == Some(key))
+}
+
+fn get_events() -> [Event; 48] {
     [
         Noop,
         DeleteChar,
@@ -81,6 +295,37 @@ fn get_events() -> [Event; 17] {
         ChangeToCreating,
         ConfirmCreation,
         ExecuteCommand,
+        ToggleOpenWith,
+        ConfirmOpenWith,
+        ChangeToCommandLine,
+        ChangeToShellCommand,
+        ConfirmCommandLine,
+        CancelCommandLine,
+        OpenShell,
+        ChangeToSettingBookmark,
+        ToggleBookmarks,
+        MoveBack,
+        MoveForward,
+        ChangeToGoingTo,
+        ChangeToJumping,
+        ChangeToFiltering,
+        ClearFilter,
+        ChangeToFinding,
+        ChangeToSearching,
+        NewTab,
+        CloseTab,
+        PreviousTab,
+        NextTab,
+        CycleSort,
+        MoveQueue,
+        CopyQueue,
+        CycleLayout,
+        SwitchPane,
+        ToggleTree,
+        ToggleExpand,
+        Refresh,
+        ToggleJobs,
+        ToggleLog,
     ]
 }
 
@@ -94,7 +339,7 @@ pub fn get_event_name(event: &Event) -> String {
         MoveUp => "(↑ | k) move up",
         MoveDown => "(↓ | j) move down",
         MoveToParent => "(← | h) move to parent",
-        MoveToChild => "(→ | l) move to child",
+        MoveToChild => "(→ | l | <Enter>) move to child / open file",
         ToggleHidden => "(.) toggle hidden",
         ToggleQueue => "(<Space>) toggle queue",
         DeleteQueue => "(d) delete queue",
@@ -103,6 +348,67 @@ pub fn get_event_name(event: &Event) -> String {
         ConfirmCreation => "(<Enter>) confirm creation",
         ExecuteCommand => "(<Enter>) execute command",
         CancelCreation => "(<Esc>) cancel creation",
+        ToggleOpenWith => "(o) open with",
+        ConfirmOpenWith => "(<Enter>) confirm opener",
+        ChangeToCommandLine => "(:) run command",
+        ChangeToShellCommand => "(!) run interactive command",
+        ConfirmCommandLine => "(<Enter>) confirm command",
+        CancelCommandLine => "(<Esc>) cancel command",
+        ToggleOutput => "(<Esc>) close output",
+        RunCustomCommand(command) => {
+            return match command.key {
+                Some(key) => format!("({}) {}", key, command.name),
+                None => command.name.clone(),
+            }
+        }
+        AcceptConfirmation => "(y) confirm",
+        RejectConfirmation => "(n) cancel",
+        OpenShell => "(S) open shell",
+        ChangeToSettingBookmark => "(m<key>) set bookmark",
+        SetBookmark(_) => "set bookmark",
+        CancelBookmark => "(<Esc>) cancel bookmark",
+        ToggleBookmarks => "(') bookmarks",
+        JumpToBookmark(_) => "jump to bookmark",
+        ConfirmBookmark => "(<Enter>) jump to bookmark",
+        DeleteBookmark => "(<Del>) delete bookmark",
+        MoveBack => "(H) go back",
+        MoveForward => "(L) go forward",
+        ChangeToGoingTo => "(g) go to path",
+        ConfirmGoTo => "(<Enter>) confirm path",
+        CancelGoTo => "(<Esc>) cancel go to",
+        CompletePath => "(<Tab>) complete path",
+        ChangeToJumping => "(z) jump to frequent directory",
+        ConfirmJump => "(<Enter>) confirm jump",
+        CancelJump => "(<Esc>) cancel jump",
+        ChangeToFiltering => "(/) filter",
+        ConfirmFilter => "(<Enter>) confirm filter",
+        ClearFilter => "(<Esc>) clear filter",
+        ChangeToFinding => "(f) find file",
+        ConfirmFind => "(<Enter>) go to file",
+        CancelFind => "(<Esc>) cancel find",
+        ChangeToSearching => "(F) search file contents",
+        ToggleRegex => "(<Tab>) toggle regex",
+        ConfirmSearch => "(<Enter>) confirm search",
+        CancelSearch => "(<Esc>) cancel search",
+        OpenResult => "(<Enter>) open result",
+        CloseResults => "(<Esc>) close results",
+        NewTab => "(t) new tab",
+        CloseTab => "(x) close tab",
+        PreviousTab => "([) previous tab",
+        NextTab => "(]) next tab",
+        CycleSort => "(s) cycle sort",
+        MoveQueue => "(p) move queue here / to other pane",
+        CopyQueue => "(c) copy queue here / to other pane",
+        CycleLayout => "(v) cycle layout",
+        SwitchPane => "(<Tab>) switch pane",
+        ToggleTree => "(T) toggle tree view",
+        ToggleExpand => "(e) expand/collapse directory",
+        Refresh => "(R) refresh listings",
+        ToggleJobs => "(J) jobs",
+        CancelJob => "(c) cancel job",
+        RetryJob => "(r) retry job",
+        ToggleLog => "(E) log",
+        CycleLogLevel => "(l) cycle log level",
         Noop => "noop",
     }
     .to_string()
@@ -119,12 +425,77 @@ pub fn in_reexecution_allow_list(event: &Event) -> bool {
             | CancelCreation
             | MoveLeft
             | MoveRight
+            | ConfirmOpenWith
+            | ConfirmCommandLine
+            | CancelCommandLine
+            | ToggleOutput
+            | AcceptConfirmation
+            | RejectConfirmation
+            | SetBookmark(_)
+            | CancelBookmark
+            | JumpToBookmark(_)
+            | ConfirmBookmark
+            | DeleteBookmark
+            | ConfirmGoTo
+            | CancelGoTo
+            | CompletePath
+            | ConfirmJump
+            | CancelJump
+            | ConfirmFilter
+            | ConfirmFind
+            | CancelFind
+            | ToggleRegex
+            | ConfirmSearch
+            | CancelSearch
+            | OpenResult
+            | CancelJob
+            | RetryJob
+            | CycleLogLevel
+            | CloseResults
     )
 }
 
-pub fn get_command_picker_events() -> Vec<Event> {
+pub fn get_command_picker_events(config: &Config) -> Vec<Event> {
     get_events()
         .into_iter()
         .filter(in_reexecution_allow_list)
+        .chain(config.commands.iter().cloned().map(RunCustomCommand))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_key(key: char) -> Config {
+        Config {
+            commands: vec![CustomCommand {
+                name: "custom".to_string(),
+                command: "true".to_string(),
+                key: Some(key),
+                confirm: false,
+                interactive: false,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn custom_keys_override_built_in_ones() {
+        let config = config_with_key('o');
+
+        let event = get_event(&Mode::Normal, &KeyCode::Char('o'), &config);
+        assert!(matches!(event, RunCustomCommand(c) if c.name == "custom"));
+
+        let event = get_event(&Mode::Normal, &KeyCode::Char('c'), &config);
+        assert!(matches!(event, CopyQueue));
+    }
+
+    #[test]
+    fn custom_keys_only_apply_in_normal_mode() {
+        let config = config_with_key('o');
+
+        let event = get_event(&Mode::Filtering, &KeyCode::Char('o'), &config);
+        assert!(matches!(event, AddChar(c) if c == "o"));
+    }
+}
@@ -0,0 +1,208 @@
+use globset::{GlobBuilder, GlobMatcher};
+use regex::{Regex, RegexBuilder};
+
+/// Narrows a listing by name, case-insensitive unless the pattern has uppercase characters.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    Substring {
+        needle: String,
+        ignore_case: bool,
+    },
+    /// `highlight` mirrors the glob with a group around everything but the wildcards.
+    Glob {
+        matcher: GlobMatcher,
+        highlight: Option<Regex>,
+    },
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Translates a glob to a regex capturing the characters matched by literals, classes and
+/// alternatives, or `None` for the few forms it does not handle (nested alternatives).
+fn get_highlight_regex(pattern: &str, ignore_case: bool) -> Option<Regex> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                regex.push_str("([");
+                let mut first = true;
+                loop {
+                    let c = chars.next()?;
+                    match c {
+                        ']' if !first => break,
+                        '!' | '^' if first => regex.push('^'),
+                        '\\' | '[' | '&' | '~' | '^' => {
+                            regex.push('\\');
+                            regex.push(c);
+                        }
+                        _ => regex.push(c),
+                    }
+                    first = false;
+                }
+                regex.push_str("])");
+            }
+            '{' => {
+                let mut alternatives = String::new();
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        '{' => return None,
+                        c => alternatives.push(c),
+                    }
+                }
+                let alternatives: Vec<String> =
+                    alternatives.split(',').map(regex::escape).collect();
+                regex.push_str(&format!("({})", alternatives.join("|")));
+            }
+            '\\' => {
+                let c = chars.next()?;
+                regex.push_str(&format!("({})", regex::escape(&c.to_string())));
+            }
+            c => regex.push_str(&format!("({})", regex::escape(&c.to_string()))),
+        }
+    }
+    regex.push('$');
+
+    RegexBuilder::new(&regex)
+        .case_insensitive(ignore_case)
+        .build()
+        .ok()
+}
+
+impl Filter {
+    pub fn new(pattern: &str) -> Option<Self> {
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let ignore_case = !pattern.chars().any(char::is_uppercase);
+
+        if is_glob(pattern) {
+            if let Ok(glob) = GlobBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .build()
+            {
+                return Some(Self::Glob {
+                    matcher: glob.compile_matcher(),
+                    highlight: get_highlight_regex(pattern, ignore_case),
+                });
+            }
+        }
+
+        let needle = if ignore_case {
+            pattern.to_lowercase()
+        } else {
+            pattern.to_string()
+        };
+
+        Some(Self::Substring {
+            needle,
+            ignore_case,
+        })
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Substring {
+                needle,
+                ignore_case,
+            } => {
+                if *ignore_case {
+                    name.to_lowercase().contains(needle.as_str())
+                } else {
+                    name.contains(needle.as_str())
+                }
+            }
+            Self::Glob { matcher, .. } => matcher.is_match(name),
+        }
+    }
+
+    /// Char indices of `name` matched by the filter, wildcards excluded.
+    pub fn get_match_indices(&self, name: &str) -> Vec<usize> {
+        let (needle, ignore_case) = match self {
+            Self::Substring {
+                needle,
+                ignore_case,
+            } => (needle, ignore_case),
+            Self::Glob { highlight, .. } => {
+                return highlight
+                    .as_ref()
+                    .map(|regex| get_capture_indices(regex, name))
+                    .unwrap_or_default()
+            }
+        };
+
+        let chars: Vec<char> = if *ignore_case {
+            name.chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .collect()
+        } else {
+            name.chars().collect()
+        };
+        let needle: Vec<char> = needle.chars().collect();
+
+        chars
+            .windows(needle.len())
+            .position(|w| w == needle.as_slice())
+            .map(|start| (start..start + needle.len()).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn get_capture_indices(regex: &Regex, name: &str) -> Vec<usize> {
+    let Some(captures) = regex.captures(name) else {
+        return Vec::new();
+    };
+
+    let ranges: Vec<_> = captures
+        .iter()
+        .skip(1)
+        .flatten()
+        .map(|m| m.range())
+        .collect();
+
+    name.char_indices()
+        .enumerate()
+        .filter(|(_, (byte, _))| ranges.iter().any(|r| r.contains(byte)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indices(pattern: &str, name: &str) -> Vec<usize> {
+        Filter::new(pattern).unwrap().get_match_indices(name)
+    }
+
+    #[test]
+    fn highlights_substrings_by_char() {
+        assert_eq!(indices("fé", "Caféfé"), vec![2, 3]);
+        assert_eq!(indices("Fe", "feFe"), vec![2, 3]);
+        assert_eq!(indices("xyz", "main.rs"), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn highlights_glob_literals_but_not_wildcards() {
+        assert_eq!(indices("*.rs", "main.rs"), vec![4, 5, 6]);
+        assert_eq!(indices("m?in*", "main.rs"), vec![0, 2, 3]);
+        assert_eq!(indices("[mn]ain.*", "nain.rs"), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn highlights_glob_alternatives() {
+        assert_eq!(indices("*.{rs,toml}", "Cargo.toml"), vec![5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn highlights_nothing_for_unsupported_globs() {
+        assert_eq!(indices("{a,{b,c}}*", "bin"), Vec::<usize>::new());
+    }
+}
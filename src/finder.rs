@@ -0,0 +1,157 @@
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use ignore::WalkBuilder;
+
+const MAX_RESULTS: usize = 500;
+
+/// Paths under `root`, collected by a background walker and ranked against a fuzzy query.
+pub struct Finder {
+    root: PathBuf,
+    paths: Vec<String>,
+    receiver: Receiver<String>,
+    done: bool,
+    matcher: SkimMatcherV2,
+    query: String,
+    scored: usize,
+    results: Vec<(i64, usize)>,
+}
+
+impl std::fmt::Debug for Finder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Finder")
+            .field("root", &self.root)
+            .field("paths", &self.paths.len())
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+fn walk(root: PathBuf, show_hidden: bool, sender: mpsc::Sender<String>) {
+    let walker = WalkBuilder::new(&root)
+        .hidden(!show_hidden)
+        .require_git(false)
+        .build();
+
+    for entry in walker.filter_map(|e| e.ok()) {
+        let Ok(relative) = entry.path().strip_prefix(&root) else {
+            continue;
+        };
+
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        if sender
+            .send(relative.to_string_lossy().into_owned())
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Higher scores first, then shorter paths.
+fn rank(paths: &[String], a: &(i64, usize), b: &(i64, usize)) -> Ordering {
+    b.0.cmp(&a.0).then(paths[a.1].len().cmp(&paths[b.1].len()))
+}
+
+impl Finder {
+    pub fn new(root: &Path, show_hidden: bool) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        let walk_root = root.to_path_buf();
+        thread::spawn(move || walk(walk_root, show_hidden, sender));
+
+        Self {
+            root: root.to_path_buf(),
+            paths: Vec::new(),
+            receiver,
+            done: false,
+            matcher: SkimMatcherV2::default().smart_case(),
+            query: String::new(),
+            scored: 0,
+            results: Vec::new(),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn get_count(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Collects the paths found since the last call.
+    pub fn receive(&mut self) {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(path) => self.paths.push(path),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.done = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Ranks the collected paths against `query`, only scoring what changed since the last call
+    /// and keeping the best `MAX_RESULTS`.
+    pub fn update(&mut self, query: &str) {
+        if query == self.query && self.scored == self.paths.len() {
+            return;
+        }
+
+        if query != self.query {
+            self.query = query.to_string();
+            self.scored = 0;
+            self.results.clear();
+        }
+
+        let mut found: Vec<(i64, usize)> = Vec::new();
+        for (index, path) in self.paths.iter().enumerate().skip(self.scored) {
+            if self.query.is_empty() {
+                found.push((0, index));
+            } else if let Some(score) = self.matcher.fuzzy_match(path, &self.query) {
+                found.push((score, index));
+            }
+        }
+        self.scored = self.paths.len();
+
+        found.sort_by(|a, b| rank(&self.paths, a, b));
+
+        // both are sorted, so merging them keeps the best results without sorting everything again
+        let mut previous = std::mem::take(&mut self.results).into_iter().peekable();
+        let mut found = found.into_iter().peekable();
+        while self.results.len() < MAX_RESULTS {
+            let next = match (previous.peek(), found.peek()) {
+                (Some(a), Some(b)) if rank(&self.paths, b, a).is_lt() => found.next(),
+                (Some(_), _) => previous.next(),
+                (None, _) => found.next(),
+            };
+
+            let Some(next) = next else {
+                break;
+            };
+            self.results.push(next);
+        }
+    }
+
+    pub fn get_results(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .map(|(_, index)| self.paths[*index].clone())
+            .collect()
+    }
+
+    pub fn get_path(&self, result: usize) -> Option<PathBuf> {
+        self.results
+            .get(result)
+            .map(|(_, index)| self.root.join(&self.paths[*index]))
+    }
+}
@@ -0,0 +1,293 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use color_eyre::{eyre::eyre, eyre::Context, Result};
+use directories::BaseDirs;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::debug::get_data_dir;
+
+lazy_static! {
+    pub static ref FRECENCY_FILE: String = "frecency.toml".to_string();
+}
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+const MAX_RANK: f64 = 10_000.0;
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dir {
+    pub path: PathBuf,
+    pub rank: f64,
+    pub last_accessed: u64,
+}
+
+/// Visited directories ranked by frequency and recency, like zoxide.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Database {
+    dirs: Vec<Dir>,
+    /// When the first visit not yet written to disk happened.
+    #[serde(skip)]
+    unsaved: Option<Instant>,
+}
+
+fn get_file_path() -> PathBuf {
+    let dir = get_data_dir();
+    dir.join(FRECENCY_FILE.clone())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl Dir {
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_accessed);
+
+        if age < HOUR {
+            self.rank * 4.0
+        } else if age < DAY {
+            self.rank * 2.0
+        } else if age < WEEK {
+            self.rank * 0.5
+        } else {
+            self.rank * 0.25
+        }
+    }
+}
+
+impl Database {
+    pub fn load() -> Self {
+        let path = get_file_path();
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Self::default(),
+        };
+
+        match toml::from_str(&content) {
+            Ok(db) => db,
+            Err(e) => {
+                tracing::error!("Could not parse frecency file {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self) {
+        let content = match toml::to_string(self) {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::error!("Could not serialize frecency database: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = std::fs::write(get_file_path(), content) {
+            tracing::error!("Could not save frecency database: {}", e);
+        }
+    }
+
+    fn add(&mut self, path: &Path, rank: f64, last_accessed: u64) {
+        match self.dirs.iter_mut().find(|d| d.path == path) {
+            Some(dir) => {
+                dir.rank += rank;
+                dir.last_accessed = dir.last_accessed.max(last_accessed);
+            }
+            None => self.dirs.push(Dir {
+                path: path.to_path_buf(),
+                rank,
+                last_accessed,
+            }),
+        }
+    }
+
+    /// Ages every entry once the total rank gets too big, forgetting the least used ones.
+    fn age(&mut self) {
+        let total: f64 = self.dirs.iter().map(|d| d.rank).sum();
+        if total <= MAX_RANK {
+            return;
+        }
+
+        let factor = 0.9 * MAX_RANK / total;
+        for dir in self.dirs.iter_mut() {
+            dir.rank *= factor;
+        }
+        self.dirs.retain(|d| d.rank >= 1.0);
+    }
+
+    pub fn visit(&mut self, path: &Path) {
+        self.add(path, 1.0, now());
+        self.age();
+        self.unsaved.get_or_insert_with(Instant::now);
+    }
+
+    /// Whether visits have waited long enough to be written, so quick navigation is saved once.
+    pub fn is_stale(&self) -> bool {
+        self.unsaved.is_some_and(|t| t.elapsed() >= SAVE_DELAY)
+    }
+
+    /// Writes the visits not yet saved.
+    pub fn flush(&mut self) {
+        if self.unsaved.take().is_some() {
+            self.save();
+        }
+    }
+
+    /// Directories matching `query`, from the highest to the lowest score.
+    pub fn query(&self, query: &str) -> Vec<PathBuf> {
+        let matcher = SkimMatcherV2::default().smart_case();
+        let now = now();
+
+        let mut dirs: Vec<(f64, &Dir)> = self
+            .dirs
+            .iter()
+            .filter(|d| d.path.is_dir())
+            .filter(|d| {
+                query.is_empty()
+                    || matcher
+                        .fuzzy_match(&d.path.to_string_lossy(), query)
+                        .is_some()
+            })
+            .map(|d| (d.score(now), d))
+            .collect();
+
+        dirs.sort_by(|a, b| b.0.total_cmp(&a.0));
+        dirs.into_iter().map(|(_, d)| d.path.clone()).collect()
+    }
+
+    /// Merges a zoxide database (`db.zo`) into this one, returning how many entries were read.
+    pub fn import_zoxide(&mut self, path: &Path) -> Result<usize> {
+        let bytes = std::fs::read(path).wrap_err("Failed to read zoxide database")?;
+        let dirs = parse_zoxide(&bytes)?;
+        let count = dirs.len();
+
+        for dir in dirs {
+            self.add(&dir.path, dir.rank, dir.last_accessed);
+        }
+        self.age();
+        self.save();
+
+        Ok(count)
+    }
+}
+
+pub fn get_zoxide_db_path() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("_ZO_DATA_DIR") {
+        return Some(PathBuf::from(dir).join("db.zo"));
+    }
+
+    BaseDirs::new().map(|dirs| dirs.data_local_dir().join("zoxide").join("db.zo"))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        if self.bytes.len() < N {
+            return Err(eyre!("Unexpected end of zoxide database"));
+        }
+
+        let (head, tail) = self.bytes.split_at(N);
+        self.bytes = tail;
+
+        Ok(head.try_into()?)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u64()? as usize;
+        if self.bytes.len() < len {
+            return Err(eyre!("Unexpected end of zoxide database"));
+        }
+
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+
+        Ok(String::from_utf8_lossy(head).into_owned())
+    }
+}
+
+/// Reads the bincode encoded `(version, Vec<Dir>)` tuple written by zoxide.
+fn parse_zoxide(bytes: &[u8]) -> Result<Vec<Dir>> {
+    let mut reader = Reader { bytes };
+
+    let version = u32::from_le_bytes(reader.take()?);
+    if version != 3 {
+        return Err(eyre!("Unsupported zoxide database version {}", version));
+    }
+
+    let len = reader.u64()?;
+    let mut dirs = Vec::new();
+    for _ in 0..len {
+        let path = PathBuf::from(reader.string()?);
+        let rank = f64::from_le_bytes(reader.take()?);
+        let last_accessed = reader.u64()?;
+
+        dirs.push(Dir {
+            path,
+            rank,
+            last_accessed,
+        });
+    }
+
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `db.zo` holding `/home/me` (rank 2.5) and `/tmp` (rank 1.0), as zoxide writes it.
+    const DB: &[u8] = &[
+        3, 0, 0, 0, // version
+        2, 0, 0, 0, 0, 0, 0, 0, // number of dirs
+        8, 0, 0, 0, 0, 0, 0, 0, // path length
+        b'/', b'h', b'o', b'm', b'e', b'/', b'm', b'e', // path
+        0, 0, 0, 0, 0, 0, 0x04, 0x40, // rank
+        0x10, 0x27, 0, 0, 0, 0, 0, 0, // last accessed
+        4, 0, 0, 0, 0, 0, 0, 0, // path length
+        b'/', b't', b'm', b'p', // path
+        0, 0, 0, 0, 0, 0, 0xf0, 0x3f, // rank
+        0x2a, 0, 0, 0, 0, 0, 0, 0, // last accessed
+    ];
+
+    #[test]
+    fn parses_zoxide_database() {
+        let dirs = parse_zoxide(DB).unwrap();
+
+        assert_eq!(dirs.len(), 2);
+        assert_eq!(dirs[0].path, PathBuf::from("/home/me"));
+        assert_eq!(dirs[0].rank, 2.5);
+        assert_eq!(dirs[0].last_accessed, 10_000);
+        assert_eq!(dirs[1].path, PathBuf::from("/tmp"));
+        assert_eq!(dirs[1].rank, 1.0);
+        assert_eq!(dirs[1].last_accessed, 42);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut db = DB.to_vec();
+        db[0] = 2;
+
+        assert!(parse_zoxide(&db).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_database() {
+        assert!(parse_zoxide(&DB[..DB.len() - 1]).is_err());
+        assert!(parse_zoxide(&DB[..20]).is_err());
+    }
+}
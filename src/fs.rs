@@ -1,26 +1,87 @@
-use color_eyre::{eyre::Context, Result};
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use directories::BaseDirs;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, DirEntry};
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-pub fn change_dir<CB>(new_path: &Path, mut cb: CB) -> Result<()>
-where
-    CB: FnMut(),
-{
-    let res = std::env::set_current_dir(new_path).wrap_err("Failed to change directory");
-    cb();
+/// The absolute form of `path`, as long as it is a directory that can be listed.
+pub fn resolve_dir(path: &Path) -> Result<PathBuf> {
+    let path = path
+        .canonicalize()
+        .wrap_err("Failed to resolve directory")?;
+    if !path.is_dir() {
+        return Err(eyre!("{:?} is not a directory", path));
+    }
+    fs::read_dir(&path).wrap_err("Failed to read directory")?;
 
-    res
+    Ok(path)
+}
+
+/// The closest directory to `path` that still exists, for when it gets deleted under us.
+pub fn get_existing_ancestor(path: &Path) -> PathBuf {
+    path.ancestors()
+        .find(|p| p.is_dir())
+        .map(Path::to_path_buf)
+        .or_else(get_home_dir)
+        .unwrap_or_default()
 }
 
 pub fn current_dir() -> Result<PathBuf> {
     std::env::current_dir().wrap_err("Failed to get the current dir")
 }
 
-pub fn dir_entry_to_string(de: &DirEntry) -> String {
-    de.file_name().into_string().unwrap()
+pub fn get_dir_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// `bytes` in the largest unit that keeps it above one, like `1.5 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// A listed file with the metadata used for sorting, read once so no directory handle is kept.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub name: String,
+    modified: Option<SystemTime>,
+    size: Option<u64>,
+}
+
+impl Entry {
+    fn new(de: &DirEntry) -> Self {
+        let metadata = de.metadata().ok();
+
+        Self {
+            path: de.path(),
+            name: de.file_name().to_string_lossy().to_string(),
+            modified: metadata.as_ref().and_then(|m| m.modified().ok()),
+            size: metadata.as_ref().map(|m| m.len()),
+        }
+    }
 }
 
-pub fn get_content<P: AsRef<Path>>(path: P, show_hidden: bool) -> Vec<DirEntry> {
+pub fn get_content<P: AsRef<Path>>(path: P, show_hidden: bool) -> Vec<Entry> {
     fs::read_dir(path)
         .map(|rd| {
             rd.filter_map(|e| e.ok())
@@ -31,6 +92,7 @@ pub fn get_content<P: AsRef<Path>>(path: P, show_hidden: bool) -> Vec<DirEntry>
                         is_not_hidden(&de.path())
                     }
                 })
+                .map(|de| Entry::new(&de))
                 .collect()
         })
         .unwrap_or_else(|_| Vec::new())
@@ -85,52 +147,595 @@ pub fn is_hidden(path: &Path) -> bool {
     false
 }
 
-pub fn delete_all(items_to_delete: Vec<PathBuf>) {
-    for qi in items_to_delete.iter() {
-        if qi.is_file() {
-            let res = std::fs::remove_file(qi);
+/// Told how many files and directories were just processed, failing to stop the operation.
+pub type Progress<'a> = &'a mut dyn FnMut(usize) -> std::io::Result<()>;
 
-            if res.is_err() {
-                tracing::error!("{:?}", res);
-            }
-            continue;
-        }
+fn is_real_dir(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|m| m.is_dir())
+}
 
-        if qi.is_dir() {
-            let res = std::fs::remove_dir_all(qi);
+/// How many files and directories there are in `path`, itself included.
+pub fn count_entries(path: &Path) -> usize {
+    if !is_real_dir(path) {
+        return 1;
+    }
 
-            if res.is_err() {
-                tracing::error!("{:?}", res);
-            }
-            continue;
+    let children = fs::read_dir(path)
+        .map(|rd| {
+            rd.filter_map(|e| e.ok())
+                .map(|e| count_entries(&e.path()))
+                .sum()
+        })
+        .unwrap_or(0);
+
+    1 + children
+}
+
+pub fn delete_path(path: &Path, progress: Progress) -> Result<()> {
+    remove_path(path, progress).wrap_err("Failed to delete")
+}
+
+/// Removes `path` one entry at a time; a symlink is removed without touching what it points to.
+fn remove_path(path: &Path, progress: Progress) -> std::io::Result<()> {
+    if is_real_dir(path) {
+        for entry in fs::read_dir(path)? {
+            remove_path(&entry?.path(), progress)?;
         }
+        fs::remove_dir(path)?;
+    } else {
+        fs::remove_file(path)?;
     }
-}
 
-pub fn create_path<P: Into<PathBuf>>(path: P) {
-    let path: &PathBuf = &path.into();
+    progress(1)
+}
 
+/// Creates a file, or a directory when `path` ends with a separator, along with its parents.
+pub fn create_path(path: &Path) -> Result<()> {
     if path
         .to_string_lossy()
         .ends_with(std::path::MAIN_SEPARATOR_STR)
     {
-        if let Err(e) = std::fs::create_dir_all(path) {
-            tracing::error!("Could not create dir {}", e);
-            return;
+        return std::fs::create_dir_all(path).wrap_err("Failed to create directory");
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).wrap_err("Failed to create parent directory")?;
+    }
+
+    std::fs::File::create(path).wrap_err("Failed to create file")?;
+
+    Ok(())
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Sort {
+    #[default]
+    Name,
+    Modified,
+    Size,
+}
+
+impl Sort {
+    pub fn next(self) -> Self {
+        match self {
+            Sort::Name => Sort::Modified,
+            Sort::Modified => Sort::Size,
+            Sort::Size => Sort::Name,
         }
     }
 
-    let parent = path.parent().unwrap();
-    if let Err(e) = std::fs::create_dir_all(parent) {
-        tracing::error!("Could not create dir {}", e);
-        return;
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Sort::Name => "name",
+            Sort::Modified => "modified",
+            Sort::Size => "size",
+        }
     }
+}
+
+/// Sorts by name, or with the newest/biggest entries first.
+pub fn sort_content(content: &mut [Entry], sort: Sort) {
+    match sort {
+        Sort::Name => content.sort_by_key(|e| e.name.to_lowercase()),
+        Sort::Modified => content.sort_by_key(|e| std::cmp::Reverse(e.modified)),
+        Sort::Size => content.sort_by_key(|e| std::cmp::Reverse(e.size)),
+    }
+}
+
+#[derive(Debug)]
+struct Listing {
+    modified: Option<SystemTime>,
+    show_hidden: bool,
+    sort: Sort,
+    entries: Vec<Entry>,
+    generation: u64,
+}
+
+/// Sorted directory listings kept between frames, read again once a directory is modified.
+#[derive(Debug, Default)]
+pub struct ListingCache {
+    listings: HashMap<PathBuf, Listing>,
+    generation: u64,
+}
+
+fn get_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl ListingCache {
+    pub fn get(&mut self, path: &Path, show_hidden: bool, sort: Sort) -> &[Entry] {
+        let modified = get_modified(path);
+        let fresh = self.listings.get(path).is_some_and(|l| {
+            modified.is_some()
+                && l.modified == modified
+                && l.show_hidden == show_hidden
+                && l.sort == sort
+        });
+
+        if !fresh {
+            let mut entries = get_content(path, show_hidden);
+            sort_content(&mut entries, sort);
+            self.generation += 1;
+
+            self.listings.insert(
+                path.to_path_buf(),
+                Listing {
+                    modified,
+                    show_hidden,
+                    sort,
+                    entries,
+                    generation: self.generation,
+                },
+            );
+        }
+
+        &self.listings[path].entries
+    }
+
+    /// Unique to each read of `path`, so views built from its listing know when to rebuild.
+    pub fn get_generation(&self, path: &Path) -> Option<u64> {
+        self.listings.get(path).map(|l| l.generation)
+    }
+
+    pub fn invalidate(&mut self, path: &Path) {
+        self.listings.remove(path);
+    }
+
+    pub fn clear(&mut self) {
+        self.listings.clear();
+    }
+
+    /// Drops the listings of the directories not in `paths`, the ones no longer on screen.
+    pub fn retain(&mut self, paths: &HashSet<PathBuf>) {
+        self.listings.retain(|path, _| paths.contains(path));
+    }
+}
+
+/// Lists `path` with the `expanded` directories' children inlined, each entry paired with its
+/// indentation guide.
+pub fn get_tree_content<F>(
+    listings: &mut ListingCache,
+    path: &Path,
+    show_hidden: bool,
+    sort: Sort,
+    expanded: &HashSet<PathBuf>,
+    keep: &F,
+) -> Vec<(PathBuf, String)>
+where
+    F: Fn(&Entry) -> bool,
+{
+    let mut tree = Vec::new();
+    add_tree_level(
+        listings,
+        path,
+        (show_hidden, sort),
+        expanded,
+        keep,
+        None,
+        &mut tree,
+    );
+
+    tree
+}
+
+fn add_tree_level<F>(
+    listings: &mut ListingCache,
+    path: &Path,
+    (show_hidden, sort): (bool, Sort),
+    expanded: &HashSet<PathBuf>,
+    keep: &F,
+    indent: Option<&str>,
+    tree: &mut Vec<(PathBuf, String)>,
+) where
+    F: Fn(&Entry) -> bool,
+{
+    let content: Vec<PathBuf> = listings
+        .get(path, show_hidden, sort)
+        .iter()
+        .filter(|e| keep(e))
+        .map(|e| e.path.clone())
+        .collect();
+
+    let count = content.len();
+    for (i, child_path) in content.into_iter().enumerate() {
+        let last = i + 1 == count;
+        let (guide, child_indent) = match indent {
+            Some(indent) if last => (format!("{}└─ ", indent), format!("{}   ", indent)),
+            Some(indent) => (format!("{}├─ ", indent), format!("{}│  ", indent)),
+            None => (String::new(), String::new()),
+        };
+
+        let open = expanded.contains(&child_path) && child_path.is_dir();
+        tree.push((child_path.clone(), guide));
+
+        if open {
+            add_tree_level(
+                listings,
+                &child_path,
+                (show_hidden, sort),
+                expanded,
+                keep,
+                Some(&child_indent),
+                tree,
+            );
+        }
+    }
+}
+
+fn get_target(item: &Path, dest: &Path) -> Result<PathBuf> {
+    let name = item
+        .file_name()
+        .ok_or_else(|| eyre!("{:?} has no file name", item))?;
+    let target = dest.join(name);
+
+    if target.exists() {
+        return Err(eyre!("{:?} already exists", target));
+    }
+
+    if dest.starts_with(item) {
+        return Err(eyre!("Cannot put {:?} inside itself", item));
+    }
+
+    Ok(target)
+}
+
+/// Moves `item` into `dest`, copying and deleting it when it is on another filesystem.
+pub fn move_into(item: &Path, dest: &Path, progress: Progress) -> Result<()> {
+    let target = get_target(item, dest)?;
+
+    match std::fs::rename(item, &target) {
+        Ok(()) => {
+            // renamed in one go, too late to stop
+            let _ = progress(count_entries(&target));
+            return Ok(());
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {}
+        Err(e) => return Err(e).wrap_err("Failed to move"),
+    }
+
+    copy_or_clean_up(item, &target, progress)?;
 
-    if let Err(e) = std::fs::File::create(path) {
-        tracing::error!("Could not create file {}", e);
+    // once copied, the original goes away even if the move is cancelled
+    delete_path(item, &mut |_| Ok(()))
+}
+
+pub fn copy_into(item: &Path, dest: &Path, progress: Progress) -> Result<()> {
+    let target = get_target(item, dest)?;
+
+    copy_or_clean_up(item, &target, progress)
+}
+
+/// Copies `from` to `to`, removing whatever was already copied if it fails or is stopped halfway.
+fn copy_or_clean_up(from: &Path, to: &Path, progress: Progress) -> Result<()> {
+    let Err(e) = copy_path(from, to, progress) else {
+        return Ok(());
     };
+
+    if std::fs::symlink_metadata(to).is_ok() {
+        if let Err(e) = delete_path(to, &mut |_| Ok(())) {
+            tracing::error!("Could not remove the partial copy {:?}: {:#}", to, e);
+        }
+    }
+
+    Err(e).wrap_err("Failed to copy")
+}
+
+/// Copies files and directories as they are, recreating symlinks instead of following them.
+fn copy_path(from: &Path, to: &Path, progress: Progress) -> std::io::Result<()> {
+    let file_type = std::fs::symlink_metadata(from)?.file_type();
+
+    if file_type.is_symlink() {
+        copy_symlink(from, to)?;
+    } else if file_type.is_dir() {
+        std::fs::create_dir(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_path(&entry.path(), &to.join(entry.file_name()), progress)?;
+        }
+    } else {
+        std::fs::copy(from, to)?;
+    }
+
+    progress(1)
+}
+
+#[cfg(target_family = "unix")]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(from)?, to)
+}
+
+#[cfg(target_family = "windows")]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    let link = std::fs::read_link(from)?;
+
+    if from.is_dir() {
+        std::os::windows::fs::symlink_dir(link, to)
+    } else {
+        std::os::windows::fs::symlink_file(link, to)
+    }
+}
+
+const BINARY_SNIFF_LEN: usize = 8192;
+
+/// Opens `path` with its first bytes already sniffed, or `None` when it looks like a binary file.
+fn open_text(path: &Path) -> Option<impl Read> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut head = Vec::new();
+    (&mut file)
+        .take(BINARY_SNIFF_LEN as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+
+    if head.contains(&0) {
+        return None;
+    }
+
+    Some(io::Cursor::new(head).chain(file))
+}
+
+/// Reads up to `limit` bytes of `path`, or `None` when it looks like a binary file.
+pub fn read_text(path: &Path, limit: u64) -> Option<String> {
+    let mut bytes = Vec::new();
+    open_text(path)?.take(limit).read_to_end(&mut bytes).ok()?;
+
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The lines of `path`, read only as they are consumed, or `None` when it looks like a binary
+/// file.
+pub fn read_lines(path: &Path) -> Option<impl Iterator<Item = String>> {
+    let lines = BufReader::new(open_text(path)?)
+        .split(b'\n')
+        .map_while(|line| line.ok())
+        .map(|mut line| {
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            String::from_utf8_lossy(&line).into_owned()
+        });
+
+    Some(lines)
 }
 
 pub fn get_delimiter() -> &'static str {
     std::path::MAIN_SEPARATOR_STR
 }
+
+pub fn get_home_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf())
+}
+
+/// Resolves `~`, relative and absolute paths typed by the user against `base`.
+pub fn expand_path(input: &str, base: &Path) -> PathBuf {
+    let expanded = match input.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(std::path::is_separator) => {
+            match get_home_dir() {
+                Some(home) => home.join(rest.trim_start_matches(std::path::is_separator)),
+                None => PathBuf::from(input),
+            }
+        }
+        _ => PathBuf::from(input),
+    };
+
+    base.join(expanded)
+}
+
+/// Completes the last component of `input` with the names of the directories that match it.
+pub fn complete_path(input: &str, base: &Path) -> Option<String> {
+    let split_at = input
+        .rfind(std::path::is_separator)
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let (dir, prefix) = input.split_at(split_at);
+
+    let dir_path = if dir.is_empty() {
+        base.to_path_buf()
+    } else {
+        expand_path(dir, base)
+    };
+
+    let candidates: Vec<String> = get_content(&dir_path, prefix.starts_with('.'))
+        .into_iter()
+        .filter(|e| e.path.is_dir())
+        .map(|e| e.name)
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+
+    let first = candidates.first()?;
+    let common = candidates.iter().fold(first.clone(), |common, name| {
+        common
+            .chars()
+            .zip(name.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect()
+    });
+
+    let completed = if candidates.len() == 1 {
+        format!("{}{}{}", dir, common, get_delimiter())
+    } else {
+        format!("{}{}", dir, common)
+    };
+
+    Some(completed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_tree(name: &str, dirs: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("fman-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for dir in dirs {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn completes_a_single_match_with_a_delimiter() {
+        let root = temp_tree("complete-single", &["café", "music"]);
+        fs::write(root.join("cafeteria.txt"), "").unwrap();
+
+        let completed = complete_path("ca", &root);
+
+        assert_eq!(completed, Some(format!("café{}", get_delimiter())));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn completes_the_common_prefix_of_several_matches() {
+        let root = temp_tree("complete-common", &["projects", "pro-old", "src/lib"]);
+
+        assert_eq!(complete_path("pr", &root), Some("pro".to_string()));
+        let nested = format!("src{}l", get_delimiter());
+        assert_eq!(
+            complete_path(&nested, &root),
+            Some(format!("src{0}lib{0}", get_delimiter()))
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn completes_hidden_directories_only_when_asked() {
+        let root = temp_tree("complete-hidden", &[".config", "code"]);
+
+        assert_eq!(
+            complete_path("c", &root),
+            Some(format!("code{}", get_delimiter()))
+        );
+        assert_eq!(
+            complete_path(".c", &root),
+            Some(format!(".config{}", get_delimiter()))
+        );
+        assert_eq!(complete_path("x", &root), None);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn reads_lines_of_text_files_only() {
+        let root = temp_tree("read-lines", &[]);
+        fs::write(root.join("text"), "one\r\ntwo\n\nthree").unwrap();
+        fs::write(root.join("binary"), b"head\0tail\n").unwrap();
+
+        let lines: Vec<String> = read_lines(&root.join("text")).unwrap().collect();
+
+        assert_eq!(lines, ["one", "two", "", "three"]);
+        assert!(read_lines(&root.join("binary")).is_none());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn copies_symlinks_as_links() {
+        let root = temp_tree("copy-symlink", &["src/dir", "dest"]);
+        fs::write(root.join("src/dir/file"), "content").unwrap();
+        std::os::unix::fs::symlink("dir", root.join("src/link")).unwrap();
+
+        copy_into(&root.join("src"), &root.join("dest"), &mut |_| Ok(())).unwrap();
+
+        let link = root.join("dest/src/link");
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_link(&link).unwrap(), PathBuf::from("dir"));
+        assert_eq!(
+            fs::read_to_string(root.join("dest/src/dir/file")).unwrap(),
+            "content"
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn deletes_symlinks_without_their_target() {
+        let root = temp_tree("delete-symlink", &["target"]);
+        fs::write(root.join("target/file"), "content").unwrap();
+        std::os::unix::fs::symlink(root.join("target"), root.join("link")).unwrap();
+
+        let mut count = 0;
+        delete_path(&root.join("link"), &mut |n| {
+            count += n;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(count, 1);
+        assert!(fs::symlink_metadata(root.join("link")).is_err());
+        assert!(root.join("target/file").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn refuses_to_move_inside_itself_or_onto_existing_names() {
+        let root = temp_tree("move-refused", &["dir/inner", "dest/dir"]);
+
+        assert!(move_into(&root.join("dir"), &root.join("dir/inner"), &mut |_| Ok(())).is_err());
+        assert!(move_into(&root.join("dir"), &root.join("dest"), &mut |_| Ok(())).is_err());
+        assert!(root.join("dir/inner").is_dir());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn moves_into_a_directory() {
+        let root = temp_tree("move", &["dir", "dest"]);
+        fs::write(root.join("dir/file"), "content").unwrap();
+
+        move_into(&root.join("dir"), &root.join("dest"), &mut |_| Ok(())).unwrap();
+
+        assert!(!root.join("dir").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("dest/dir/file")).unwrap(),
+            "content"
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn cleans_up_a_stopped_copy() {
+        let root = temp_tree("copy-stopped", &["src/a", "src/b", "dest"]);
+        fs::write(root.join("src/a/file"), "content").unwrap();
+        fs::write(root.join("src/b/file"), "content").unwrap();
+
+        let mut copied = 0;
+        let result = copy_into(&root.join("src"), &root.join("dest"), &mut |n| {
+            copied += n;
+            if copied >= 2 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "cancelled",
+                ));
+            }
+            Ok(())
+        });
+
+        assert!(result.is_err());
+        assert!(!root.join("dest/src").exists());
+        assert!(root.join("src/a/file").exists());
+        assert!(root.join("src/b/file").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
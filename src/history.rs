@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+/// A visited directory and the entry that was selected in it.
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub path: PathBuf,
+    pub selected: Option<PathBuf>,
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    back: Vec<Location>,
+    forward: Vec<Location>,
+}
+
+impl History {
+    pub fn push(&mut self, location: Location) {
+        self.back.push(location);
+        self.forward.clear();
+    }
+
+    pub fn back(&mut self, current: Location) -> Option<Location> {
+        let previous = self.back.pop()?;
+        self.forward.push(current);
+
+        Some(previous)
+    }
+
+    pub fn forward(&mut self, current: Location) -> Option<Location> {
+        let next = self.forward.pop()?;
+        self.back.push(current);
+
+        Some(next)
+    }
+}
@@ -0,0 +1,303 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use color_eyre::Result;
+
+use crate::fs;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    Delete,
+    Create,
+    Copy(PathBuf),
+    Move(PathBuf),
+}
+
+impl Operation {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Operation::Delete => "delete",
+            Operation::Create => "create",
+            Operation::Copy(_) => "copy",
+            Operation::Move(_) => "move",
+        }
+    }
+
+    fn run(&self, item: &Path, progress: fs::Progress) -> Result<()> {
+        match self {
+            Operation::Delete => fs::delete_path(item, progress),
+            Operation::Create => {
+                fs::create_path(item)?;
+                let _ = progress(1);
+                Ok(())
+            }
+            Operation::Copy(dest) => fs::copy_into(item, dest, progress),
+            Operation::Move(dest) => fs::move_into(item, dest, progress),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Update {
+    Counted {
+        id: usize,
+        entries: usize,
+    },
+    Progress {
+        id: usize,
+        entries: usize,
+    },
+    Done {
+        id: usize,
+        item: PathBuf,
+        error: Option<String>,
+    },
+    Finished {
+        id: usize,
+    },
+}
+
+/// A file operation applied to a list of items by a worker thread.
+#[derive(Debug)]
+pub struct Job {
+    pub id: usize,
+    pub operation: Operation,
+    pub items: Vec<PathBuf>,
+    pub completed: Vec<PathBuf>,
+    pub errors: Vec<(PathBuf, String)>,
+    /// Files and directories in the items, known once the worker has walked them.
+    pub entries: usize,
+    pub entries_done: usize,
+    pub started: Instant,
+    pub finished: Option<Instant>,
+    pub cancelled: bool,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Job {
+    pub fn get_done(&self) -> usize {
+        self.completed.len() + self.errors.len()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.finished.is_none()
+    }
+
+    /// Files and directories processed per second.
+    pub fn get_throughput(&self) -> f64 {
+        let elapsed = self.finished.unwrap_or_else(Instant::now) - self.started;
+
+        self.entries_done as f64 / elapsed.as_secs_f64().max(0.001)
+    }
+
+    pub fn get_eta(&self) -> Option<Duration> {
+        let throughput = self.get_throughput();
+        if !self.is_running() || self.entries == 0 || throughput <= 0.0 {
+            return None;
+        }
+
+        let remaining = self.entries.saturating_sub(self.entries_done);
+        Some(Duration::from_secs_f64(remaining as f64 / throughput))
+    }
+}
+
+#[derive(Debug)]
+pub struct Jobs {
+    jobs: Vec<Job>,
+    sender: Sender<Update>,
+    receiver: Receiver<Update>,
+    next_id: usize,
+    newly_finished: Vec<usize>,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            jobs: Vec::new(),
+            sender,
+            receiver,
+            next_id: 0,
+            newly_finished: Vec::new(),
+        }
+    }
+}
+
+fn work(
+    id: usize,
+    operation: Operation,
+    items: Vec<PathBuf>,
+    cancel: Arc<AtomicBool>,
+    sender: Sender<Update>,
+) {
+    let entries = items.iter().map(|item| fs::count_entries(item)).sum();
+    if sender.send(Update::Counted { id, entries }).is_err() {
+        return;
+    }
+
+    let mut progress = |entries| {
+        let _ = sender.send(Update::Progress { id, entries });
+
+        if cancel.load(Ordering::Relaxed) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "cancelled",
+            ));
+        }
+
+        Ok(())
+    };
+
+    for item in items {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let result = operation.run(&item, &mut progress);
+
+        // an item stopped halfway is left for a retry, neither done nor failed
+        if result.is_err() && cancel.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let error = match result {
+            Ok(()) => None,
+            Err(e) => {
+                tracing::error!("Could not {} {:?}: {:#}", operation.get_name(), item, e);
+                Some(format!("{:#}", e))
+            }
+        };
+
+        if sender.send(Update::Done { id, item, error }).is_err() {
+            return;
+        }
+    }
+
+    let _ = sender.send(Update::Finished { id });
+}
+
+impl Jobs {
+    pub fn spawn(&mut self, operation: Operation, items: Vec<PathBuf>) {
+        if items.is_empty() {
+            return;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let sender = self.sender.clone();
+        let work_operation = operation.clone();
+        let work_items = items.clone();
+        let work_cancel = cancel.clone();
+        thread::spawn(move || work(id, work_operation, work_items, work_cancel, sender));
+
+        self.jobs.push(Job {
+            id,
+            operation,
+            items,
+            completed: Vec::new(),
+            errors: Vec::new(),
+            entries: 0,
+            entries_done: 0,
+            started: Instant::now(),
+            finished: None,
+            cancelled: false,
+            cancel,
+        });
+    }
+
+    /// The indices of the jobs that finished since the last call.
+    pub fn take_finished(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.newly_finished)
+    }
+
+    pub fn get_jobs(&self) -> &Vec<Job> {
+        &self.jobs
+    }
+
+    /// Stops a running job before its next file.
+    pub fn cancel(&mut self, index: usize) {
+        let Some(job) = self.jobs.get_mut(index).filter(|j| j.is_running()) else {
+            return;
+        };
+
+        job.cancel.store(true, Ordering::Relaxed);
+        job.cancelled = true;
+    }
+
+    /// Runs a finished job again on the items it did not complete.
+    pub fn retry(&mut self, index: usize) {
+        let Some(job) = self.jobs.get(index).filter(|j| !j.is_running()) else {
+            return;
+        };
+
+        let items = job
+            .items
+            .iter()
+            .filter(|item| !job.completed.contains(item))
+            .cloned()
+            .collect();
+        let operation = job.operation.clone();
+
+        self.spawn(operation, items);
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.jobs.iter().any(Job::is_running)
+    }
+
+    pub fn get_running(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter().filter(|j| j.is_running())
+    }
+
+    /// Collects the progress made since the last call, returning the items that were completed.
+    pub fn receive(&mut self) -> Vec<(Operation, PathBuf)> {
+        let mut completed = Vec::new();
+
+        loop {
+            let update = match self.receiver.try_recv() {
+                Ok(update) => update,
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => return completed,
+            };
+
+            match update {
+                Update::Counted { id, entries } => {
+                    if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+                        job.entries = entries;
+                    }
+                }
+                Update::Progress { id, entries } => {
+                    if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+                        job.entries_done += entries;
+                    }
+                }
+                Update::Done { id, item, error } => {
+                    let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) else {
+                        continue;
+                    };
+
+                    match error {
+                        Some(error) => job.errors.push((item, error)),
+                        None => {
+                            completed.push((job.operation.clone(), item.clone()));
+                            job.completed.push(item);
+                        }
+                    }
+                }
+                Update::Finished { id } => {
+                    if let Some(index) = self.jobs.iter().position(|j| j.id == id) {
+                        self.jobs[index].finished = Some(Instant::now());
+                        self.newly_finished.push(index);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,30 +1,65 @@
+mod bookmarks;
+mod config;
 mod debug;
 mod event;
+mod filter;
+mod finder;
+mod frecency;
 mod fs;
+mod history;
+mod jobs;
+mod open;
+mod search;
+mod shell;
+mod status;
 mod ui;
+mod watch;
 
+use crate::debug::{get_data_dir, LOG_ENV, LOG_FILE};
+use bookmarks::Bookmarks;
 use clap::Parser;
 use color_eyre::{eyre::Context, Result};
+use config::{Config, Opener};
 use event::Event;
-use ratatui::crossterm::event::read;
+use filter::Filter;
+use finder::Finder;
+use frecency::Database;
+use history::History;
 use ratatui::crossterm::event::Event::Key;
 use ratatui::crossterm::event::KeyEvent;
+use ratatui::crossterm::event::{poll, read};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{enable_raw_mode, EnterAlternateScreen};
 use ratatui::prelude::*;
-use ratatui::widgets::{Clear, ListState, Paragraph};
+use ratatui::widgets::{Clear, ListState};
 use ratatui::{crossterm::event::KeyEventKind, widgets::Block, DefaultTerminal, Frame};
-use std::collections::HashSet;
+use search::Search;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
-use std::fs::{DirEntry, File};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
 use tracing::Level;
 use tracing_appender::non_blocking;
-use tracing_subscriber::EnvFilter;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::fmt::time;
-use crate::debug::get_dir_and_log_file_path;
+use tracing_subscriber::EnvFilter;
+
+const PREVIEW_LIMIT: u64 = 64 * 1024;
+const TICK: Duration = Duration::from_millis(100);
+const MAX_LOG_FILES: usize = 7;
 
 #[derive(Parser, Debug, Default)]
 #[command(version, long_about = None)]
-struct Args {}
+struct Args {
+    /// Import a zoxide database into the jump history, defaults to zoxide's own database
+    #[arg(long, value_name = "DB")]
+    import_zoxide: Option<Option<PathBuf>>,
+
+    /// Log level or filter directives, overrides FMAN_LOGLEVEL and the config file
+    #[arg(long, value_name = "LEVEL")]
+    log_level: Option<String>,
+}
 
 #[derive(Debug, Default)]
 struct App {
@@ -32,9 +67,33 @@ struct App {
     should_quit: bool,
     mode: Mode,
     input: Input,
-    left_rect_list: EntriesList,
+    tabs: Vec<Tab>,
+    active_tab: usize,
+    other_tab: usize,
+    layout: config::Layout,
     queued_items: HashSet<PathBuf>,
     command_list: CommandList,
+    opener_list: OpenerList,
+    output: Output,
+    pending_confirmation: Option<Event>,
+    bookmarks: Bookmarks,
+    bookmark_list: ListState,
+    cursor_memory: HashMap<PathBuf, PathBuf>,
+    frecency: Database,
+    jump_list: JumpList,
+    finder: Option<Finder>,
+    finder_list: ListState,
+    search: Option<Search>,
+    search_list: ListState,
+    search_regex: bool,
+    config: Config,
+    foreground_command: Option<ForegroundCommand>,
+    listings: fs::ListingCache,
+    watcher: watch::Watcher,
+    jobs: jobs::Jobs,
+    job_list: ListState,
+    notification: Option<status::Notification>,
+    log_viewer: LogViewer,
 }
 
 #[derive(Debug, PartialEq, Default)]
@@ -43,6 +102,20 @@ enum Mode {
     Normal,
     Creating,
     ShowingCommands,
+    OpeningWith,
+    CommandLine,
+    ShowingOutput,
+    Confirming,
+    SettingBookmark,
+    ShowingBookmarks,
+    GoingTo,
+    Jumping,
+    Filtering,
+    Finding,
+    Searching,
+    ShowingResults,
+    ShowingJobs,
+    ShowingLog,
 }
 
 #[derive(Debug, Default)]
@@ -57,93 +130,512 @@ struct CommandList {
     state: ListState,
 }
 
+#[derive(Debug, Default)]
+struct OpenerList {
+    path: PathBuf,
+    items: Vec<Opener>,
+    state: ListState,
+}
+
+#[derive(Debug, Default)]
+struct JumpList {
+    items: Vec<PathBuf>,
+    state: ListState,
+}
+
+#[derive(Debug, Default)]
+struct Output {
+    title: String,
+    lines: Vec<String>,
+    scroll: u16,
+    execution: Option<shell::Execution>,
+}
+
+#[derive(Debug, Default)]
+struct LogViewer {
+    level: Option<Level>,
+    scroll_back: usize,
+    tail: debug::LogTail,
+    /// The lines shown for `level`, filtered again when the log or the level changes.
+    lines: Vec<String>,
+    lines_level: Option<Option<Level>>,
+}
+
+impl LogViewer {
+    fn get_lines(&mut self) -> &[String] {
+        if self.tail.refresh() || self.lines_level != Some(self.level) {
+            let level = self.level;
+            self.lines = self
+                .tail
+                .get_lines()
+                .iter()
+                .filter(|(l, _)| level.is_none_or(|level| l.is_none_or(|l| l <= level)))
+                .map(|(_, line)| line.clone())
+                .collect();
+            self.lines_level = Some(level);
+        }
+
+        &self.lines
+    }
+}
+
+#[derive(Debug)]
+struct ForegroundCommand {
+    command: Command,
+    wait_for_key: bool,
+}
+
 impl Input {
     fn new(text: String) -> Self {
         Self {
-            char_index: text.len(),
+            char_index: text.chars().count(),
             text,
         }
     }
 }
 
+/// What a tab's entries were built from, so they are only rebuilt once it changes.
+#[derive(Debug, PartialEq)]
+struct View {
+    path: PathBuf,
+    pattern: String,
+    show_hidden: bool,
+    sort: fs::Sort,
+    tree: bool,
+    expanded: HashSet<PathBuf>,
+    generations: Vec<(PathBuf, u64)>,
+}
+
 #[derive(Debug, Default)]
 struct EntriesList {
-    items: Vec<DirEntry>,
+    view: Option<View>,
+    items: Vec<PathBuf>,
+    names: Vec<String>,
+    guides: Vec<String>,
+    filter: Option<Filter>,
     state: ListState,
+    pending_selection: Option<PathBuf>,
+}
+
+/// A working directory with its own listing, filter, sort and history.
+#[derive(Debug, Default)]
+struct Tab {
+    path: PathBuf,
+    list: EntriesList,
+    filter: String,
+    sort: fs::Sort,
+    tree: bool,
+    expanded: HashSet<PathBuf>,
+    history: History,
+}
+
+impl Tab {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            ..Default::default()
+        }
+    }
+}
+
+impl EntriesList {
+    fn get_selected_path(&self) -> Option<PathBuf> {
+        self.state
+            .selected()
+            .and_then(|index| self.items.get(index))
+            .cloned()
+    }
 }
 
 impl App {
-    pub fn with_args() -> Self {
+    pub fn with_config(config: Config) -> Self {
+        let path = fs::current_dir().unwrap_or_else(|_| fs::get_home_dir().unwrap_or_default());
+
+        let mut tabs = vec![Tab::new(path.clone())];
+        if config.layout == config::Layout::Dual {
+            tabs.push(Tab::new(path));
+        }
+
         Self {
             should_quit: false,
             show_hidden: false,
             mode: Mode::default(),
             input: Input::default(),
-            left_rect_list: EntriesList::default(),
+            other_tab: tabs.len() - 1,
+            tabs,
+            active_tab: 0,
+            layout: config.layout,
             queued_items: HashSet::new(),
             command_list: CommandList::default(),
+            opener_list: OpenerList::default(),
+            output: Output::default(),
+            pending_confirmation: None,
+            bookmarks: Bookmarks::load(),
+            bookmark_list: ListState::default(),
+            cursor_memory: HashMap::new(),
+            frecency: Database::load(),
+            jump_list: JumpList::default(),
+            finder: None,
+            finder_list: ListState::default(),
+            search: None,
+            search_list: ListState::default(),
+            search_regex: false,
+            config,
+            foreground_command: None,
+            listings: fs::ListingCache::default(),
+            watcher: watch::Watcher::default(),
+            jobs: jobs::Jobs::default(),
+            job_list: ListState::default(),
+            notification: None,
+            log_viewer: LogViewer::default(),
         }
     }
 
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        let mut redraw = true;
         while !self.should_quit {
-            terminal.draw(|frame| self.draw(frame))?;
-            if let Key(key) = read()? {
-                self.handle_key(key);
-            };
+            if redraw {
+                terminal.draw(|frame| self.draw(frame))?;
+                let visible = self.get_visible_dirs();
+                self.listings.retain(&visible);
+                self.watcher.watch(visible);
+            }
+
+            // keep redrawing while background work streams in results or a notification times out
+            redraw = self.is_busy() || self.notification.is_some() || self.mode == Mode::ShowingLog;
+
+            // waking up on every tick picks up the watcher's changes without a keypress
+            if poll(TICK)? {
+                if let Key(key) = read()? {
+                    self.handle_key(key);
+                };
+                redraw = true;
+            }
+
+            let changed = self.watcher.receive();
+            for path in changed.iter() {
+                self.listings.invalidate(path);
+            }
+            redraw |= !changed.is_empty();
+
+            let completed = self.jobs.receive();
+            for (operation, item) in completed.iter() {
+                if matches!(
+                    operation,
+                    jobs::Operation::Copy(_) | jobs::Operation::Move(_) | jobs::Operation::Delete
+                ) {
+                    self.queued_items.remove(item);
+                }
+            }
+            if !completed.is_empty() {
+                self.listings.clear();
+                redraw = true;
+            }
+
+            for index in self.jobs.take_finished() {
+                let (level, message) = summarize_job(&self.jobs.get_jobs()[index]);
+                self.notify(level, message);
+            }
+
+            if self.frecency.is_stale() {
+                self.frecency.flush();
+            }
+
+            if let Some(foreground) = self.foreground_command.take() {
+                let program = foreground
+                    .command
+                    .get_program()
+                    .to_string_lossy()
+                    .into_owned();
+                if let Err(e) = run_in_foreground(&mut terminal, foreground)? {
+                    self.notify(
+                        status::Level::Error,
+                        format!("Could not run {}: {}", program, e),
+                    );
+                }
+                redraw = true;
+            }
         }
 
+        self.frecency.flush();
+
         Ok(())
     }
 
+    fn notify(&mut self, level: status::Level, message: String) {
+        match level {
+            status::Level::Success => tracing::info!("{}", message),
+            status::Level::Warning => tracing::warn!("{}", message),
+            status::Level::Error => tracing::error!("{}", message),
+        }
+
+        self.notification = Some(status::Notification::new(level, message));
+    }
+
+    fn tab(&self) -> &Tab {
+        &self.tabs[self.active_tab]
+    }
+
+    fn tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active_tab]
+    }
+
+    fn is_busy(&self) -> bool {
+        self.finder.as_ref().is_some_and(|f| !f.is_done())
+            || self.search.as_ref().is_some_and(|s| !s.is_done())
+            || self.jobs.is_busy()
+            || self.output.execution.as_ref().is_some_and(|e| !e.is_done())
+    }
+
+    /// The directories on screen, watched for changes made by other programs.
+    fn get_visible_dirs(&self) -> HashSet<PathBuf> {
+        let mut dirs = HashSet::new();
+
+        let mut visible = vec![self.active_tab];
+        if self.layout == config::Layout::Dual {
+            visible.push(self.other_tab);
+        }
+
+        for tab in visible.into_iter().map(|index| &self.tabs[index]) {
+            dirs.insert(tab.path.clone());
+            if tab.tree {
+                dirs.extend(
+                    tab.expanded
+                        .iter()
+                        .filter(|p| p.starts_with(&tab.path))
+                        .cloned(),
+                );
+            }
+        }
+
+        if self.layout == config::Layout::Miller {
+            let list = &self.tab().list;
+            if let Some(parent) = self.tab().path.parent() {
+                dirs.insert(parent.to_path_buf());
+            }
+            if let Some(path) = list.state.selected().and_then(|i| list.items.get(i)) {
+                if path.is_dir() {
+                    dirs.insert(path.clone());
+                }
+            }
+        }
+
+        dirs
+    }
+
     fn draw(&mut self, frame: &mut Frame) {
-        let current_path = fs::current_dir().unwrap();
+        for tab in self.tabs.iter_mut() {
+            if !tab.path.is_dir() {
+                tab.path = fs::get_existing_ancestor(&tab.path);
+            }
+        }
+        let current_path = self.tab().path.clone();
 
-        let [left_rect, right] = Layout::horizontal([Constraint::Fill(1); 2]).areas(frame.area());
+        let mut area = frame.area();
+        if self.tabs.len() > 1 {
+            let [tabs_rect, rest] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+            let titles = self
+                .tabs
+                .iter()
+                .map(|t| fs::get_dir_name(&t.path))
+                .collect();
 
-        let current_path_content: Vec<String> = self
-            .update_content(fs::get_content(&current_path, self.show_hidden))
-            .iter()
-            .map(fs::dir_entry_to_string)
-            .collect();
+            frame.render_widget(ui::TabBar::new(titles, self.active_tab), tabs_rect);
+            area = rest;
+        }
+
+        let [rest, status_rect] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        area = rest;
 
-        let list = ui::MainList::new(current_path.display().to_string(), current_path_content);
+        let running: Vec<&jobs::Job> = self.jobs.get_running().collect();
+        if !running.is_empty() {
+            let [rest, progress_rect] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
 
-        frame.render_stateful_widget(list, left_rect, &mut self.left_rect_list.state);
-        frame.render_widget(
-            Block::bordered()
-                .title("content")
-                .border_type(ratatui::widgets::BorderType::Rounded),
-            right,
-        );
+            let done = running.iter().map(|j| j.entries_done).sum();
+            let total = running.iter().map(|j| j.entries).sum();
+            let label = match running.as_slice() {
+                [job] => format!("{} {}/{} files", job.operation.get_name(), done, total),
+                _ => format!("{} jobs {}/{} files", running.len(), done, total),
+            };
+
+            frame.render_widget(ui::ProgressLine::new(label, done, total), progress_rect);
+            area = rest;
+        }
+
+        if self.layout == config::Layout::Miller {
+            let [parent_rect, rest] =
+                Layout::horizontal([Constraint::Fill(1), Constraint::Fill(4)]).areas(area);
+
+            self.draw_parent(frame, &current_path, parent_rect);
+            area = rest;
+        }
+
+        let [left_rect, right] = Layout::horizontal([Constraint::Fill(1); 2]).areas(area);
+
+        self.draw_tab(frame, self.active_tab, left_rect, true);
+
+        match self.layout {
+            config::Layout::Single => frame.render_widget(
+                Block::bordered()
+                    .title("content")
+                    .border_type(ratatui::widgets::BorderType::Rounded),
+                right,
+            ),
+            config::Layout::Dual => self.draw_tab(frame, self.other_tab, right, false),
+            config::Layout::Miller => self.draw_preview(frame, right),
+        }
+
+        self.draw_status(frame, &current_path, status_rect);
 
         match self.mode {
             Mode::Creating => {
-                let input = tui_input::Input::new(self.input.text.clone());
-                let area = frame.area();
-                let rect = Rect {
-                    x: area.width / 4,
-                    y: area.height / 3,
-                    width: area.width / 2,
-                    height: 3,
-                };
                 let delimiter = fs::get_delimiter();
-                let block = Block::bordered()
-                    .title_top(format!("create item or folders ({} ended)", delimiter))
-                    .border_type(ratatui::widgets::BorderType::Rounded);
-                let scroll_offset = rect.width - 2;
-                let scroll = input.visual_scroll(scroll_offset.into()) as u16;
-                let p = Paragraph::new(input.value())
-                    .block(block)
-                    .scroll((0, scroll));
-
-                frame.render_widget(Clear, rect);
-                frame.render_widget(p, rect);
+                let popup = ui::InputPopup::new(
+                    format!("create item or folders ({} ended)", delimiter),
+                    self.input.text.clone(),
+                );
+
+                frame.render_widget(popup, frame.area());
+            }
+            Mode::CommandLine => {
+                let popup = ui::InputPopup::new(
+                    "run command (%f file, %s queue, %d dir, ! prefix for interactive)".to_string(),
+                    self.input.text.clone(),
+                );
+
+                frame.render_widget(popup, frame.area());
+            }
+            Mode::GoingTo => {
+                let popup = ui::InputPopup::new(
+                    "go to (<Tab> to complete)".to_string(),
+                    self.input.text.clone(),
+                );
+
+                frame.render_widget(popup, frame.area());
+            }
+            Mode::Jumping => {
+                self.jump_list.items = self.frecency.query(&self.input.text);
+                if self.jump_list.state.selected().is_none() {
+                    self.jump_list.state.select_first();
+                }
+
+                let jump_selector = ui::SearchPicker::new(
+                    "jump to directory".to_string(),
+                    self.input.text.clone(),
+                    self.jump_list
+                        .items
+                        .iter()
+                        .map(|p| p.display().to_string())
+                        .collect(),
+                );
+
+                frame.render_stateful_widget(
+                    jump_selector,
+                    frame.area(),
+                    &mut self.jump_list.state,
+                );
+            }
+            Mode::Finding => {
+                if let Some(finder) = self.finder.as_mut() {
+                    finder.receive();
+                    finder.update(&self.input.text);
+
+                    let title = if finder.is_done() {
+                        format!("find ({} paths)", finder.get_count())
+                    } else {
+                        format!("find ({} paths, scanning...)", finder.get_count())
+                    };
+                    let finder_selector =
+                        ui::SearchPicker::new(title, self.input.text.clone(), finder.get_results());
+
+                    frame.render_stateful_widget(
+                        finder_selector,
+                        frame.area(),
+                        &mut self.finder_list,
+                    );
+                }
+            }
+            Mode::Searching => {
+                let title = if self.search_regex {
+                    "search contents (regex, <Tab> for literal)"
+                } else {
+                    "search contents (literal, <Tab> for regex)"
+                };
+                let popup = ui::InputPopup::new(title.to_string(), self.input.text.clone());
+
+                frame.render_widget(popup, frame.area());
+            }
+            Mode::ShowingResults => {
+                if let Some(search) = self.search.as_mut() {
+                    search.receive();
+
+                    let matches = search.get_matches();
+                    let title = if search.is_truncated() {
+                        format!(
+                            "{} ({}+ matches (truncated))",
+                            search.get_pattern(),
+                            matches.len()
+                        )
+                    } else if search.is_done() {
+                        format!("{} ({} matches)", search.get_pattern(), matches.len())
+                    } else {
+                        format!(
+                            "{} ({} matches, searching...)",
+                            search.get_pattern(),
+                            matches.len()
+                        )
+                    };
+                    let results = matches
+                        .iter()
+                        .map(|m| format!("{}:{}: {}", m.path.display(), m.line, m.text))
+                        .collect();
+
+                    let selected = self.search_list.selected().filter(|&i| i < matches.len());
+                    let preview = match selected {
+                        Some(i) => {
+                            let name = matches[i].path.display().to_string();
+                            let height = right.height.saturating_sub(2) as usize;
+                            let (lines, index) = search.get_context(i, height / 2);
+                            ui::Preview::new(name, lines.to_vec(), Some(index))
+                        }
+                        None => ui::Preview::new("preview".to_string(), Vec::new(), None),
+                    };
+
+                    frame.render_widget(Clear, left_rect);
+                    frame.render_stateful_widget(
+                        ui::ResultList::new(title, results),
+                        left_rect,
+                        &mut self.search_list,
+                    );
+                    frame.render_widget(Clear, right);
+                    frame.render_widget(preview, right);
+                }
+            }
+            Mode::ShowingOutput => {
+                let mut title = self.output.title.clone();
+                if let Some(execution) = self.output.execution.as_mut() {
+                    execution.receive(&mut self.output.lines);
+
+                    match execution.get_status() {
+                        Some(status) => title.push_str(&format!(" ({})", status)),
+                        None => title.push_str(" (running, <Esc> to interrupt)"),
+                    }
+                }
+
+                let popup =
+                    ui::OutputPopup::new(title, self.output.lines.clone(), self.output.scroll);
+
+                frame.render_widget(popup, frame.area());
             }
             Mode::ShowingCommands => {
-                let events = event::get_command_picker_events();
-                let command_selector =
-                    ui::CommandPicker::new(events.iter().map(event::get_event_name).collect());
+                let events = event::get_command_picker_events(&self.config);
+                let command_selector = ui::CommandPicker::new(
+                    "command list",
+                    events.iter().map(event::get_event_name).collect(),
+                );
 
                 self.command_list.items = events;
                 frame.render_stateful_widget(
@@ -152,49 +644,482 @@ impl App {
                     &mut self.command_list.state,
                 );
             }
+            Mode::OpeningWith => {
+                let opener_selector = ui::CommandPicker::new(
+                    "open with",
+                    self.opener_list
+                        .items
+                        .iter()
+                        .map(Opener::get_name)
+                        .collect(),
+                );
+
+                frame.render_stateful_widget(
+                    opener_selector,
+                    frame.area(),
+                    &mut self.opener_list.state,
+                );
+            }
+            Mode::Confirming => {
+                let question = match &self.pending_confirmation {
+                    Some(pending) => format!("{}? (y/n)", event::get_event_name(pending)),
+                    None => String::new(),
+                };
+                let popup = ui::InputPopup::new("confirm".to_string(), question);
+
+                frame.render_widget(popup, frame.area());
+            }
+            Mode::SettingBookmark => {
+                let popup = ui::InputPopup::new(
+                    "set bookmark".to_string(),
+                    format!("press a key to bookmark {}", current_path.display()),
+                );
+
+                frame.render_widget(popup, frame.area());
+            }
+            Mode::ShowingBookmarks => {
+                let bookmark_selector =
+                    ui::CommandPicker::new("bookmarks", self.bookmarks.get_names());
+
+                frame.render_stateful_widget(
+                    bookmark_selector,
+                    frame.area(),
+                    &mut self.bookmark_list,
+                );
+            }
+            Mode::ShowingLog => {
+                let level = self.log_viewer.level;
+                let page_height = ui::LogPopup::get_page_height(&frame.area());
+                let count = self.log_viewer.get_lines().len();
+                self.log_viewer.scroll_back = self
+                    .log_viewer
+                    .scroll_back
+                    .min(count.saturating_sub(page_height));
+
+                let title = match level {
+                    Some(level) => format!("log ({} and above)", level),
+                    None => "log".to_string(),
+                };
+
+                let popup =
+                    ui::LogPopup::new(title, &self.log_viewer.lines, self.log_viewer.scroll_back);
+                frame.render_widget(popup, frame.area());
+            }
+            Mode::ShowingJobs => {
+                let jobs = self.jobs.get_jobs();
+                let errors = self
+                    .job_list
+                    .selected()
+                    .and_then(|index| jobs.get(index.min(jobs.len().saturating_sub(1))))
+                    .map(|job| {
+                        job.errors
+                            .iter()
+                            .map(|(item, error)| format!("{}: {}", item.display(), error))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let panel = ui::JobsPanel::new(jobs.iter().map(describe_job).collect(), errors);
+                frame.render_stateful_widget(panel, frame.area(), &mut self.job_list);
+            }
             _ => (),
         }
     }
 
+    fn draw_status(&mut self, frame: &mut Frame, current_path: &Path, area: Rect) {
+        if self.notification.as_ref().is_some_and(|n| n.is_expired()) {
+            self.notification = None;
+        }
+
+        let list = &self.tab().list;
+        let mut info = vec![
+            current_path.display().to_string(),
+            format!("{} items", list.items.len()),
+        ];
+        if !self.queued_items.is_empty() {
+            info.push(format!("{} queued", self.queued_items.len()));
+        }
+
+        if let Some(path) = list.state.selected().and_then(|i| list.items.get(i)) {
+            let name = fs::get_dir_name(path);
+            info.push(match std::fs::metadata(path) {
+                Ok(m) if m.is_dir() => format!("{}/", name),
+                Ok(m) => format!("{} {}", name, fs::format_size(m.len())),
+                Err(_) => name,
+            });
+        }
+
+        let notification = self
+            .notification
+            .as_ref()
+            .map(|n| (n.level, n.message.clone()));
+        frame.render_widget(ui::StatusBar::new(info, notification), area);
+    }
+
+    fn draw_tab(&mut self, frame: &mut Frame, index: usize, area: Rect, focused: bool) {
+        let tab = &self.tabs[index];
+
+        let pattern = if focused && self.mode == Mode::Filtering {
+            &self.input.text
+        } else {
+            &tab.filter
+        };
+
+        // reads the directory again if it was modified, changing its generation
+        self.listings.get(&tab.path, self.show_hidden, tab.sort);
+
+        let fresh = tab.list.view.as_ref().is_some_and(|view| {
+            view.path == tab.path
+                && view.pattern == *pattern
+                && view.show_hidden == self.show_hidden
+                && view.sort == tab.sort
+                && view.tree == tab.tree
+                && view.expanded == tab.expanded
+                && view.generations.iter().all(|(path, generation)| {
+                    self.listings.get_generation(path) == Some(*generation)
+                })
+        });
+
+        if !fresh {
+            let view = View {
+                path: tab.path.clone(),
+                pattern: pattern.clone(),
+                show_hidden: self.show_hidden,
+                sort: tab.sort,
+                tree: tab.tree,
+                expanded: tab.expanded.clone(),
+                generations: Vec::new(),
+            };
+            self.update_content(index, view);
+        }
+
+        let tab = &mut self.tabs[index];
+        let sort = Some(tab.sort)
+            .filter(|s| *s != fs::Sort::default())
+            .map(fs::Sort::get_name);
+
+        let list = &mut tab.list;
+        let filter = list
+            .filter
+            .as_ref()
+            .zip(list.view.as_ref())
+            .map(|(f, view)| (view.pattern.as_str(), f));
+
+        let widget = ui::MainList::new(
+            tab.path.display().to_string(),
+            &list.names,
+            filter,
+            sort,
+            focused,
+        )
+        .with_guides(&list.guides);
+
+        frame.render_stateful_widget(widget, area, &mut list.state);
+    }
+
+    fn draw_parent(&mut self, frame: &mut Frame, current_path: &Path, area: Rect) {
+        let Some(parent) = current_path.parent() else {
+            frame.render_widget(Block::bordered(), area);
+            return;
+        };
+
+        let names: Vec<String> = self
+            .listings
+            .get(parent, self.show_hidden, self.tabs[self.active_tab].sort)
+            .iter()
+            .map(|e| e.name.clone())
+            .collect();
+        let current_name = fs::get_dir_name(current_path);
+        let mut state =
+            ListState::default().with_selected(names.iter().position(|name| *name == current_name));
+
+        let list = ui::MainList::new(parent.display().to_string(), &names, None, None, false);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn draw_preview(&mut self, frame: &mut Frame, area: Rect) {
+        let list = &self.tab().list;
+        let Some(entry) = list
+            .state
+            .selected()
+            .and_then(|index| list.items.get(index))
+        else {
+            frame.render_widget(ui::Preview::new(String::new(), Vec::new(), None), area);
+            return;
+        };
+
+        let path = entry.clone();
+        let height = area.height.saturating_sub(2) as usize;
+
+        let lines = if path.is_dir() {
+            let sort = self.tab().sort;
+            self.listings
+                .get(&path, self.show_hidden, sort)
+                .iter()
+                .map(|e| e.name.clone())
+                .collect()
+        } else {
+            match fs::read_text(&path, PREVIEW_LIMIT) {
+                Some(text) => text
+                    .lines()
+                    .take(height)
+                    .map(|l| l.replace('\t', "    "))
+                    .collect(),
+                None => vec!["binary file".to_string()],
+            }
+        };
+
+        let preview = ui::Preview::new(fs::get_dir_name(&path), lines, None);
+        frame.render_widget(preview, area);
+    }
+
     fn handle_key(&mut self, key: KeyEvent) {
         if key.kind != KeyEventKind::Press {
             return;
         }
 
-        let event = event::get_event(&self.mode, &key.code);
+        let event = event::get_event(&self.mode, &key.code, &self.config);
         event::handle_event(&event, self);
     }
 
-    fn update_content(&mut self, content: Vec<DirEntry>) -> &Vec<DirEntry> {
-        self.left_rect_list.items = content;
-        &self.left_rect_list.items
+    /// Lists the entries of a tab again, now described by `view`.
+    fn update_content(&mut self, index: usize, mut view: View) {
+        let filter = Filter::new(&view.pattern);
+        let keep = |e: &fs::Entry| filter.as_ref().is_none_or(|f| f.matches(&e.name));
+
+        let (items, guides): (Vec<PathBuf>, Vec<String>) = if view.tree {
+            fs::get_tree_content(
+                &mut self.listings,
+                &view.path,
+                view.show_hidden,
+                view.sort,
+                &view.expanded,
+                &keep,
+            )
+            .into_iter()
+            .unzip()
+        } else {
+            let items = self
+                .listings
+                .get(&view.path, view.show_hidden, view.sort)
+                .iter()
+                .filter(|e| keep(e))
+                .map(|e| e.path.clone())
+                .collect();
+
+            (items, Vec::new())
+        };
+
+        // only the listings the entries came from can make them stale
+        let expanded = view
+            .expanded
+            .iter()
+            .filter(|p| view.tree && p.starts_with(&view.path));
+        view.generations = std::iter::once(&view.path)
+            .chain(expanded)
+            .filter_map(|p| self.listings.get_generation(p).map(|g| (p.clone(), g)))
+            .collect();
+
+        let list = &mut self.tabs[index].list;
+
+        if list.view.as_ref().is_some_and(|v| v.path == view.path) {
+            if let Some(selected) = list.get_selected_path() {
+                self.cursor_memory.insert(view.path.clone(), selected);
+            }
+        }
+
+        let selected = list
+            .pending_selection
+            .take()
+            .or_else(|| self.cursor_memory.get(&view.path).cloned());
+
+        list.names = items.iter().map(|p| fs::get_dir_name(p)).collect();
+        list.items = items;
+        list.guides = guides;
+        list.filter = filter;
+        list.view = Some(view);
+
+        if let Some(selected) = selected {
+            if let Some(index) = list.items.iter().position(|p| *p == selected) {
+                list.state.select(Some(index));
+            }
+        }
+    }
+}
+
+fn summarize_job(job: &jobs::Job) -> (status::Level, String) {
+    let name = job.operation.get_name();
+    let total = job.items.len();
+
+    if job.cancelled {
+        (
+            status::Level::Warning,
+            format!(
+                "{} cancelled after {} of {} items",
+                name,
+                job.get_done(),
+                total
+            ),
+        )
+    } else if !job.errors.is_empty() {
+        (
+            status::Level::Error,
+            format!(
+                "{} failed for {} of {} items (J for details)",
+                name,
+                job.errors.len(),
+                total
+            ),
+        )
+    } else {
+        (
+            status::Level::Success,
+            format!("{} done for {} items", name, total),
+        )
+    }
+}
+
+fn describe_job(job: &jobs::Job) -> String {
+    let status = if job.is_running() {
+        "running"
+    } else if job.cancelled {
+        "cancelled"
+    } else if !job.errors.is_empty() {
+        "failed"
+    } else {
+        "done"
+    };
+
+    let mut line = format!(
+        "[{}] {} {}/{} items, {}/{} files ({:.1} files/s)",
+        status,
+        job.operation.get_name(),
+        job.get_done(),
+        job.items.len(),
+        job.entries_done,
+        job.entries,
+        job.get_throughput()
+    );
+    if let Some(eta) = job.get_eta() {
+        line.push_str(&format!(" eta {}s", eta.as_secs()));
+    }
+    if !job.errors.is_empty() {
+        line.push_str(&format!(" {} errors", job.errors.len()));
+    }
+
+    line
+}
+
+/// Hands the terminal over to `command` until it exits, returning the error if it did not start.
+fn run_in_foreground(
+    terminal: &mut DefaultTerminal,
+    foreground: ForegroundCommand,
+) -> Result<std::io::Result<()>> {
+    let ForegroundCommand {
+        mut command,
+        wait_for_key,
+    } = foreground;
+
+    ratatui::restore();
+
+    let started = command.status().map(|_| ());
+
+    if wait_for_key {
+        println!("\nPress <Enter> to return to fman");
+        let _ = std::io::stdin().read_line(&mut String::new());
+    }
+
+    enable_raw_mode()?;
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    terminal.clear()?;
+
+    Ok(started)
+}
+
+fn import_zoxide(path: Option<PathBuf>) -> Result<()> {
+    let path = path
+        .or_else(frecency::get_zoxide_db_path)
+        .ok_or_else(|| color_eyre::eyre::eyre!("could not find the zoxide database"))?;
+
+    let count = frecency::Database::load().import_zoxide(&path)?;
+    println!("imported {} directories from {}", count, path.display());
+
+    Ok(())
+}
+
+/// Filters by the `--log-level` flag, then `FMAN_LOGLEVEL`, then the config file, falling back
+/// to the default level with an explanation when the directives are invalid.
+fn get_env_filter(flag: Option<&str>, config: Option<&str>) -> (EnvFilter, Option<String>) {
+    let directives = flag
+        .map(String::from)
+        .or_else(|| std::env::var(LOG_ENV.as_str()).ok())
+        .or_else(|| config.map(String::from))
+        .unwrap_or_default();
+
+    let builder = EnvFilter::builder().with_default_directive(Level::DEBUG.into());
+    match builder.parse(&directives) {
+        Ok(filter) => (filter, None),
+        Err(e) => (
+            builder.parse_lossy(""),
+            Some(format!("Invalid log level {:?}: {}", directives, e)),
+        ),
     }
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
+    let args = Args::parse();
     let result = time::OffsetTime::local_rfc_3339();
-    let (dir, file_path) = get_dir_and_log_file_path();
-    std::fs::create_dir_all(dir)?;
-
-    let file = File::create(file_path).wrap_err("failed to create log file")?;
-    let (non_blocking, _guard) = non_blocking(file);
+    let dir = get_data_dir();
+    std::fs::create_dir_all(&dir)?;
 
-    let env_filter = EnvFilter::builder()
-        .with_default_directive(Level::DEBUG.into())
-        .from_env_lossy();
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE.clone())
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .wrap_err("failed to create log file")?;
+    let (non_blocking, _guard) = non_blocking(appender);
 
-    tracing_subscriber::fmt()
+    let (env_filter, mut invalid_level) = get_env_filter(args.log_level.as_deref(), None);
+    let subscriber = tracing_subscriber::fmt()
         .with_file(true)
         .with_ansi(false)
         .with_timer(result.expect("could not get local time offset"))
         .with_line_number(true)
         .with_writer(non_blocking)
         .with_env_filter(env_filter)
-        .init();
+        .with_filter_reloading();
+    let filter_handle = subscriber.reload_handle();
+    subscriber.init();
+
+    // the config is loaded once logging works, so its own errors are not lost
+    let (config, invalid_config) = config::load();
+    if let Some(level) = config.log_level.as_deref() {
+        let (env_filter, invalid) = get_env_filter(args.log_level.as_deref(), Some(level));
+        invalid_level = invalid;
+        if let Err(e) = filter_handle.reload(env_filter) {
+            tracing::error!("Could not apply the configured log level: {}", e);
+        }
+    }
+
+    if let Some(message) = invalid_level.as_deref() {
+        tracing::warn!("{}", message);
+    }
+
+    if let Some(path) = args.import_zoxide {
+        return import_zoxide(path);
+    }
+
+    let mut app = App::with_config(config);
+    if let Some(message) = invalid_config {
+        app.notification = Some(status::Notification::new(status::Level::Error, message));
+    } else if let Some(message) = invalid_level {
+        app.notification = Some(status::Notification::new(status::Level::Warning, message));
+    }
 
     let terminal = ratatui::init();
-    let app_result = App::with_args().run(terminal).context("app loop failed");
+    let app_result = app.run(terminal).context("app loop failed");
 
     ratatui::restore();
 
@@ -0,0 +1,158 @@
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use globset::Glob;
+use mime_guess::mime;
+
+use crate::config::{Config, Opener};
+
+pub fn get_mime(path: &Path) -> mime::Mime {
+    if let Some(guess) = mime_guess::from_path(path).first() {
+        return guess;
+    }
+
+    if looks_like_text(path) {
+        mime::TEXT_PLAIN
+    } else {
+        mime::APPLICATION_OCTET_STREAM
+    }
+}
+
+fn looks_like_text(path: &Path) -> bool {
+    let mut buf = [0; 1024];
+
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read(&mut buf))
+        .map(|read| !buf[..read].contains(&0))
+        .unwrap_or(false)
+}
+
+fn matches(opener: &Opener, path: &Path, mime: &mime::Mime) -> bool {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let by_extension = opener
+        .extensions
+        .iter()
+        .any(|e| e.trim_start_matches('.').to_lowercase() == extension);
+
+    let by_mime = opener.mime.iter().any(|m| match m.split_once('/') {
+        Some((top, "*")) => top == mime.type_(),
+        _ => m == mime.essence_str(),
+    });
+
+    let by_glob = opener.globs.iter().any(|g| match Glob::new(g) {
+        Ok(glob) => {
+            let matcher = glob.compile_matcher();
+            matcher.is_match(path) || path.file_name().is_some_and(|n| matcher.is_match(n))
+        }
+        Err(e) => {
+            tracing::error!("Invalid opener glob {:?}: {}", g, e);
+            false
+        }
+    });
+
+    by_extension || by_mime || by_glob
+}
+
+pub fn get_editor() -> Option<String> {
+    std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+}
+
+pub fn get_system_opener() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_family = "windows") {
+        "cmd /C start \"\""
+    } else {
+        "xdg-open"
+    }
+}
+
+/// Openers that apply to `path`, the first one being the default.
+pub fn get_openers(config: &Config, path: &Path) -> Vec<Opener> {
+    let mime = get_mime(path);
+
+    let mut openers: Vec<Opener> = config
+        .openers
+        .iter()
+        .filter(|o| matches(o, path, &mime))
+        .cloned()
+        .collect();
+
+    let editor = get_editor().map(|command| Opener {
+        name: Some(format!("{} ($EDITOR)", command)),
+        command,
+        extensions: Vec::new(),
+        globs: Vec::new(),
+        mime: Vec::new(),
+        detached: false,
+    });
+    let system = Opener {
+        name: None,
+        command: get_system_opener().to_string(),
+        extensions: Vec::new(),
+        globs: Vec::new(),
+        mime: Vec::new(),
+        detached: true,
+    };
+
+    if mime.type_() == mime::TEXT {
+        openers.extend(editor);
+        openers.push(system);
+    } else {
+        openers.push(system);
+        openers.extend(editor);
+    }
+
+    openers
+}
+
+pub fn get_command(opener: &Opener, path: &Path) -> Result<Command> {
+    let words = shell_words::split(&opener.command)
+        .wrap_err_with(|| format!("Invalid opener command {:?}", opener.command))?;
+
+    let (program, args) = words
+        .split_first()
+        .ok_or_else(|| eyre!("Empty opener command"))?;
+
+    let mut command = Command::new(program);
+    command.args(args).arg(path);
+
+    Ok(command)
+}
+
+/// `$VISUAL`/`$EDITOR` opening `path` at `line`, using the widely supported `+line` argument.
+pub fn get_editor_command(path: &Path, line: usize) -> Option<Command> {
+    let words = shell_words::split(&get_editor()?).ok()?;
+    let (program, args) = words.split_first()?;
+
+    let mut command = Command::new(program);
+    command.args(args).arg(format!("+{}", line)).arg(path);
+
+    Some(command)
+}
+
+/// Starts `command` without a terminal, waiting on it in the background so it is reaped.
+pub fn spawn_detached(mut command: Command) -> std::io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
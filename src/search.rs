@@ -0,0 +1,190 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use color_eyre::Result;
+use ignore::WalkBuilder;
+use regex::{Regex, RegexBuilder};
+
+use crate::fs;
+
+const MAX_MATCHES: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub path: PathBuf,
+    pub line: usize,
+    pub text: String,
+}
+
+/// Lines matching a pattern in the files under `root`, collected by a background worker.
+#[derive(Debug)]
+pub struct Search {
+    root: PathBuf,
+    pattern: String,
+    matches: Vec<Match>,
+    receiver: Receiver<Match>,
+    done: bool,
+    context: Option<Context>,
+}
+
+/// The lines shown around a match, kept while it stays selected.
+#[derive(Debug)]
+struct Context {
+    index: usize,
+    radius: usize,
+    lines: Vec<String>,
+    line_index: usize,
+}
+
+/// Builds a smart-case regex, escaping `pattern` unless `is_regex` is set.
+pub fn build_regex(pattern: &str, is_regex: bool) -> Result<Regex> {
+    let source = if is_regex {
+        pattern.to_string()
+    } else {
+        regex::escape(pattern)
+    };
+
+    let regex = RegexBuilder::new(&source)
+        .case_insensitive(!pattern.chars().any(char::is_uppercase))
+        .build()?;
+
+    Ok(regex)
+}
+
+fn scan(root: PathBuf, show_hidden: bool, regex: Regex, sender: mpsc::Sender<Match>) {
+    let walker = WalkBuilder::new(&root)
+        .hidden(!show_hidden)
+        .require_git(false)
+        .build();
+
+    let mut count = 0;
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+
+        let Some(lines) = fs::read_lines(entry.path()) else {
+            continue;
+        };
+        let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+
+        for (index, line) in lines.enumerate() {
+            if !regex.is_match(&line) {
+                continue;
+            }
+
+            let found = Match {
+                path: relative.to_path_buf(),
+                line: index + 1,
+                text: line.trim().to_string(),
+            };
+            if sender.send(found).is_err() {
+                return;
+            }
+
+            count += 1;
+            if count >= MAX_MATCHES {
+                return;
+            }
+        }
+    }
+}
+
+impl Search {
+    pub fn new(root: &Path, show_hidden: bool, pattern: &str, is_regex: bool) -> Result<Self> {
+        let regex = build_regex(pattern, is_regex)?;
+        let (sender, receiver) = mpsc::channel();
+
+        let scan_root = root.to_path_buf();
+        thread::spawn(move || scan(scan_root, show_hidden, regex, sender));
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            pattern: pattern.to_string(),
+            matches: Vec::new(),
+            receiver,
+            done: false,
+            context: None,
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn get_pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn get_matches(&self) -> &Vec<Match> {
+        &self.matches
+    }
+
+    /// Whether the search stopped at `MAX_MATCHES` before scanning everything.
+    pub fn is_truncated(&self) -> bool {
+        self.matches.len() >= MAX_MATCHES
+    }
+
+    pub fn get_path(&self, found: &Match) -> PathBuf {
+        self.root.join(&found.path)
+    }
+
+    /// The lines around the match at `index` and the index of the matching line among them,
+    /// reading the file only when the selection or `radius` changes.
+    pub fn get_context(&mut self, index: usize, radius: usize) -> (&[String], usize) {
+        let cached = self
+            .context
+            .as_ref()
+            .is_some_and(|c| c.index == index && c.radius == radius);
+
+        if !cached {
+            let (lines, line_index) = match self.matches.get(index) {
+                Some(found) => read_context(&self.get_path(found), found.line, radius),
+                None => (Vec::new(), 0),
+            };
+
+            self.context = Some(Context {
+                index,
+                radius,
+                lines,
+                line_index,
+            });
+        }
+
+        match self.context.as_ref() {
+            Some(context) => (&context.lines, context.line_index),
+            None => (&[], 0),
+        }
+    }
+
+    /// Collects the matches found since the last call.
+    pub fn receive(&mut self) {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(found) => self.matches.push(found),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.done = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// The lines around `line` (1-based) in `path`, and the index of `line` among them.
+fn read_context(path: &Path, line: usize, radius: usize) -> (Vec<String>, usize) {
+    let Some(content) = fs::read_lines(path) else {
+        return (Vec::new(), 0);
+    };
+
+    let start = line.saturating_sub(radius + 1);
+    let lines = content
+        .skip(start)
+        .take(radius * 2 + 1)
+        .map(|l| l.replace('\t', "    "))
+        .collect();
+
+    (lines, line - 1 - start)
+}
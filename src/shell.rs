@@ -0,0 +1,295 @@
+use std::io::{BufRead, BufReader, Read};
+#[cfg(target_family = "unix")]
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+use lazy_static::lazy_static;
+
+use crate::debug::PROJECT_NAME;
+
+lazy_static! {
+    pub static ref LEVEL_ENV: String = format!("{}_LEVEL", PROJECT_NAME.clone());
+}
+
+/// Paths available to command templates.
+#[derive(Debug, Default)]
+pub struct Selection {
+    pub file: Option<PathBuf>,
+    pub queued: Vec<PathBuf>,
+    pub dir: PathBuf,
+}
+
+/// Quotes `path` for the shell `get_command` runs, `cmd` on Windows and `sh` elsewhere.
+fn quote(path: &Path) -> String {
+    if cfg!(target_family = "windows") {
+        // Windows paths cannot contain double quotes, so there is nothing to escape
+        format!("\"{}\"", path.to_string_lossy())
+    } else {
+        shell_words::quote(&path.to_string_lossy()).into_owned()
+    }
+}
+
+/// Replaces `%f` (selected file), `%s` (queued items), `%d` (current dir) and `%%`.
+pub fn expand(template: &str, selection: &Selection) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('f') => result.push_str(&selection.file.as_deref().map(quote).unwrap_or_default()),
+            Some('s') => {
+                let queued: Vec<String> = selection.queued.iter().map(|p| quote(p)).collect();
+                result.push_str(&queued.join(" "))
+            }
+            Some('d') => result.push_str(&quote(&selection.dir)),
+            Some('%') => result.push('%'),
+            Some(other) => {
+                result.push('%');
+                result.push(other);
+            }
+            None => result.push('%'),
+        }
+    }
+
+    result
+}
+
+pub fn get_command(script: &str, dir: &Path) -> Command {
+    let mut command = if cfg!(target_family = "windows") {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(script);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    };
+
+    command.current_dir(dir);
+    command
+}
+
+/// A non-interactive command running in the background, its output collected line by line.
+#[derive(Debug)]
+pub struct Execution {
+    child: Child,
+    receiver: Receiver<String>,
+    status: Option<String>,
+}
+
+fn read_lines<R: Read>(reader: R, sender: Sender<String>) {
+    for line in BufReader::new(reader).split(b'\n') {
+        let Ok(mut line) = line else {
+            return;
+        };
+
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+
+        if sender
+            .send(String::from_utf8_lossy(&line).into_owned())
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+impl Execution {
+    pub fn new(script: &str, dir: &Path) -> std::io::Result<Self> {
+        let mut command = get_command(script, dir);
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        #[cfg(target_family = "unix")]
+        command.process_group(0);
+
+        let mut child = command.spawn()?;
+
+        let (sender, receiver) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            let sender = sender.clone();
+            thread::spawn(move || read_lines(stdout, sender));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            thread::spawn(move || read_lines(stderr, sender));
+        }
+
+        Ok(Self {
+            child,
+            receiver,
+            status: None,
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.status.is_some()
+    }
+
+    /// The exit status, once the command is done.
+    pub fn get_status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// Appends the lines printed since the last call to `lines`.
+    pub fn receive(&mut self, lines: &mut Vec<String>) {
+        let closed = loop {
+            match self.receiver.try_recv() {
+                Ok(line) => lines.push(line),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+
+        if self.status.is_none() && closed {
+            match self.child.try_wait() {
+                Ok(Some(status)) => self.status = Some(status.to_string()),
+                Ok(None) => {}
+                Err(e) => self.status = Some(e.to_string()),
+            }
+        }
+    }
+
+    /// The command runs in its own process group, so this reaches its children too.
+    #[cfg(target_family = "unix")]
+    fn kill_group(&self) -> bool {
+        let group = format!("-{}", self.child.id());
+        Command::new("kill")
+            .args(["-s", "KILL", "--", &group])
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+    }
+
+    #[cfg(target_family = "windows")]
+    fn kill_group(&self) -> bool {
+        false
+    }
+
+    pub fn kill(&mut self) {
+        if self.is_done() {
+            return;
+        }
+
+        if !self.kill_group() {
+            if let Err(e) = self.child.kill() {
+                tracing::error!("Could not interrupt command: {}", e);
+            }
+        }
+
+        self.status = Some(match self.child.wait() {
+            Ok(status) => status.to_string(),
+            Err(e) => e.to_string(),
+        });
+    }
+}
+
+fn get_shell_program() -> String {
+    if let Ok(shell) = std::env::var("SHELL") {
+        return shell;
+    }
+
+    if cfg!(target_family = "windows") {
+        std::env::var("COMSPEC").unwrap_or_else(|_| "cmd".to_string())
+    } else {
+        "sh".to_string()
+    }
+}
+
+/// An interactive shell in `dir`, with `FMAN_LEVEL` telling how deeply nested it is.
+pub fn get_subshell(dir: &Path) -> Command {
+    let level = std::env::var(LEVEL_ENV.as_str())
+        .ok()
+        .and_then(|l| l.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let mut command = Command::new(get_shell_program());
+    command
+        .current_dir(dir)
+        .env(LEVEL_ENV.as_str(), (level + 1).to_string());
+
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection() -> Selection {
+        Selection {
+            file: Some(PathBuf::from("/tmp/a b.txt")),
+            queued: vec![PathBuf::from("/tmp/x"), PathBuf::from("/tmp/y's")],
+            dir: PathBuf::from("/tmp"),
+        }
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn expands_placeholders() {
+        assert_eq!(expand("cat %f", &selection()), "cat '/tmp/a b.txt'");
+        assert_eq!(
+            expand("tar cf out.tar %s", &selection()),
+            r#"tar cf out.tar /tmp/x '/tmp/y'\''s'"#
+        );
+        assert_eq!(expand("ls %d", &selection()), "ls /tmp");
+    }
+
+    #[cfg(target_family = "windows")]
+    #[test]
+    fn expands_placeholders_for_cmd() {
+        let selection = Selection {
+            file: Some(PathBuf::from(r"C:\Users\me\a b.txt")),
+            queued: vec![PathBuf::from(r"C:\x"), PathBuf::from(r"C:\y z")],
+            dir: PathBuf::from(r"C:\Users"),
+        };
+
+        assert_eq!(
+            expand("type %f", &selection),
+            r#"type "C:\Users\me\a b.txt""#
+        );
+        assert_eq!(expand("echo %s", &selection), r#"echo "C:\x" "C:\y z""#);
+        assert_eq!(expand("dir %d", &selection), r#"dir "C:\Users""#);
+    }
+
+    #[test]
+    fn keeps_escaped_and_unknown_percents() {
+        assert_eq!(expand("echo 100%% %q %", &selection()), "echo 100% %q %");
+    }
+
+    #[test]
+    fn missing_selection_expands_to_nothing() {
+        let selection = Selection {
+            dir: PathBuf::from("/tmp"),
+            ..Default::default()
+        };
+
+        assert_eq!(expand("echo %f|%s|", &selection), "echo ||");
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn keeps_reading_past_invalid_utf8() {
+        let script = r"printf 'ok\n\377bad\n'; echo after";
+        let mut execution = Execution::new(script, Path::new("/")).unwrap();
+
+        let mut lines = Vec::new();
+        while !execution.is_done() {
+            execution.receive(&mut lines);
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        assert_eq!(lines, ["ok", "\u{FFFD}bad", "after"]);
+        assert_eq!(execution.get_status(), Some("exit status: 0"));
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Success,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn get_timeout(self) -> Duration {
+        match self {
+            Level::Success => Duration::from_secs(3),
+            Level::Warning => Duration::from_secs(5),
+            Level::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+/// A message shown in the status bar until it times out.
+#[derive(Debug)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    expires: Instant,
+}
+
+impl Notification {
+    pub fn new(level: Level, message: String) -> Self {
+        Self {
+            level,
+            message,
+            expires: Instant::now() + level.get_timeout(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires
+    }
+}
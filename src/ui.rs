@@ -1,26 +1,70 @@
 use ratatui::{
-    layout::Rect,
-    style::{palette::tailwind::SLATE, Modifier, Style},
-    widgets::{Block, Clear, List, ListState, StatefulWidget, Widget},
+    layout::{Constraint, Layout, Rect},
+    style::{
+        palette::tailwind::{GREEN, RED, SLATE, YELLOW},
+        Modifier, Style,
+    },
+    text::{Line, Span},
+    widgets::{Block, Clear, LineGauge, List, ListState, Paragraph, StatefulWidget, Tabs, Widget},
 };
 
+use crate::filter::Filter;
 use crate::fs;
+use crate::status::Level;
 
 pub const SELECTED_STYLE: Style = Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD);
+pub const UNFOCUSED_STYLE: Style = Style::new().fg(SLATE.c500);
+pub const UNFOCUSED_SELECTED_STYLE: Style = Style::new().bg(SLATE.c900);
+pub const MATCH_STYLE: Style = Style::new().fg(YELLOW.c400).add_modifier(Modifier::BOLD);
 
-pub struct MainList {
+pub struct MainList<'a> {
     current_path: String,
-    current_path_content: Vec<String>,
+    current_path_content: &'a [String],
+    filter: Option<(&'a str, &'a Filter)>,
+    sort: Option<&'static str>,
+    focused: bool,
+    guides: &'a [String],
 }
 
-impl MainList {
-    pub fn new(current_path: String, current_path_content: Vec<String>) -> Self {
+impl<'a> MainList<'a> {
+    pub fn new(
+        current_path: String,
+        current_path_content: &'a [String],
+        filter: Option<(&'a str, &'a Filter)>,
+        sort: Option<&'static str>,
+        focused: bool,
+    ) -> Self {
         Self {
             current_path,
             current_path_content,
+            filter,
+            sort,
+            focused,
+            guides: &[],
         }
     }
 
+    /// Indentation drawn before each entry, for the tree view.
+    pub fn with_guides(mut self, guides: &'a [String]) -> Self {
+        self.guides = guides;
+        self
+    }
+
+    fn highlight(name: &str, filter: &Filter) -> Line<'static> {
+        let indices = filter.get_match_indices(name);
+
+        name.chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if indices.contains(&i) {
+                    Span::styled(c.to_string(), MATCH_STYLE)
+                } else {
+                    Span::raw(c.to_string())
+                }
+            })
+            .collect()
+    }
+
     fn title(current_path: String) -> String {
         let sep = fs::get_delimiter();
 
@@ -35,19 +79,54 @@ impl MainList {
     }
 }
 
-impl StatefulWidget for MainList {
+impl StatefulWidget for MainList<'_> {
     type State = ListState;
 
     fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer, state: &mut Self::State) {
         let title = MainList::title(self.current_path);
 
-        let block = Block::bordered()
+        let mut block = Block::bordered()
             .title(title)
             .border_type(ratatui::widgets::BorderType::Rounded);
+
+        if let Some(sort) = self.sort {
+            block = block.title_top(Line::from(format!("by {}", sort)).right_aligned());
+        }
+
+        let mut items: Vec<Line> = match &self.filter {
+            Some((pattern, filter)) => {
+                block = block.title_bottom(format!("/{}", pattern));
+                self.current_path_content
+                    .iter()
+                    .map(|name| MainList::highlight(name, filter))
+                    .collect()
+            }
+            None => self
+                .current_path_content
+                .iter()
+                .map(|name| Line::from(name.as_str()))
+                .collect(),
+        };
+
+        for (line, guide) in items.iter_mut().zip(self.guides) {
+            line.spans
+                .insert(0, Span::styled(guide.as_str(), UNFOCUSED_STYLE));
+        }
+
+        if !self.focused {
+            block = block.border_style(UNFOCUSED_STYLE);
+        }
+
+        let highlight_style = if self.focused {
+            SELECTED_STYLE
+        } else {
+            UNFOCUSED_SELECTED_STYLE
+        };
+
         StatefulWidget::render(
-            List::new(self.current_path_content)
+            List::new(items)
                 .block(block)
-                .highlight_style(SELECTED_STYLE)
+                .highlight_style(highlight_style)
                 .highlight_spacing(ratatui::widgets::HighlightSpacing::Always),
             area,
             buf,
@@ -57,12 +136,16 @@ impl StatefulWidget for MainList {
 }
 
 pub struct CommandPicker {
+    title: String,
     commands: Vec<String>,
 }
 
 impl CommandPicker {
-    pub fn new(commands: Vec<String>) -> Self {
-        Self { commands }
+    pub fn new(title: &str, commands: Vec<String>) -> Self {
+        Self {
+            title: title.to_string(),
+            commands,
+        }
     }
 
     fn get_rect(area: &Rect) -> Rect {
@@ -86,7 +169,7 @@ impl StatefulWidget for CommandPicker {
         Clear.render(rect, buf);
 
         let block = Block::bordered()
-            .title("command list")
+            .title(self.title)
             .border_type(ratatui::widgets::BorderType::Rounded);
 
         StatefulWidget::render(
@@ -100,3 +183,405 @@ impl StatefulWidget for CommandPicker {
         )
     }
 }
+
+pub struct InputPopup {
+    title: String,
+    text: String,
+}
+
+impl InputPopup {
+    pub fn new(title: String, text: String) -> Self {
+        Self { title, text }
+    }
+
+    fn get_rect(area: &Rect) -> Rect {
+        Rect {
+            x: area.width / 4,
+            y: area.height / 3,
+            width: area.width / 2,
+            height: 3,
+        }
+    }
+
+    fn render_at(self, rect: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let input = tui_input::Input::new(self.text);
+
+        let block = Block::bordered()
+            .title_top(self.title)
+            .border_type(ratatui::widgets::BorderType::Rounded);
+        let scroll_offset = rect.width.saturating_sub(2);
+        let scroll = input.visual_scroll(scroll_offset.into()) as u16;
+        let p = Paragraph::new(input.value())
+            .block(block)
+            .scroll((0, scroll));
+
+        Clear.render(rect, buf);
+        p.render(rect, buf);
+    }
+}
+
+impl Widget for InputPopup {
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let rect = Self::get_rect(&area);
+        self.render_at(rect, buf);
+    }
+}
+
+pub struct OutputPopup {
+    title: String,
+    lines: Vec<String>,
+    scroll: u16,
+}
+
+impl OutputPopup {
+    pub fn new(title: String, lines: Vec<String>, scroll: u16) -> Self {
+        Self {
+            title,
+            lines,
+            scroll,
+        }
+    }
+
+    fn get_rect(area: &Rect) -> Rect {
+        Rect {
+            x: area.width / 8,
+            y: area.height / 8,
+            width: area.width * 3 / 4,
+            height: area.height * 3 / 4,
+        }
+    }
+}
+
+impl Widget for OutputPopup {
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let rect = Self::get_rect(&area);
+
+        let block = Block::bordered()
+            .title(self.title)
+            .border_type(ratatui::widgets::BorderType::Rounded);
+        let p = Paragraph::new(self.lines.join("\n"))
+            .block(block)
+            .scroll((self.scroll, 0));
+
+        Clear.render(rect, buf);
+        p.render(rect, buf);
+    }
+}
+
+pub struct SearchPicker {
+    title: String,
+    query: String,
+    items: Vec<String>,
+}
+
+impl SearchPicker {
+    pub fn new(title: String, query: String, items: Vec<String>) -> Self {
+        Self {
+            title,
+            query,
+            items,
+        }
+    }
+
+    fn get_rect(area: &Rect) -> Rect {
+        Rect {
+            x: area.width / 8,
+            y: area.height / 8,
+            width: area.width * 3 / 4,
+            height: area.height * 3 / 4,
+        }
+    }
+}
+
+impl StatefulWidget for SearchPicker {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer, state: &mut Self::State)
+    where
+        Self: Sized,
+    {
+        let rect = Self::get_rect(&area);
+        let [input_rect, list_rect] =
+            Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(rect);
+
+        Clear.render(rect, buf);
+        InputPopup::new(self.title, self.query).render_at(input_rect, buf);
+
+        let block = Block::bordered().border_type(ratatui::widgets::BorderType::Rounded);
+
+        StatefulWidget::render(
+            List::new(self.items)
+                .block(block)
+                .highlight_style(SELECTED_STYLE)
+                .highlight_spacing(ratatui::widgets::HighlightSpacing::Always),
+            list_rect,
+            buf,
+            state,
+        )
+    }
+}
+
+pub struct ResultList {
+    title: String,
+    results: Vec<String>,
+}
+
+impl ResultList {
+    pub fn new(title: String, results: Vec<String>) -> Self {
+        Self { title, results }
+    }
+}
+
+impl StatefulWidget for ResultList {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer, state: &mut Self::State) {
+        let block = Block::bordered()
+            .title(self.title)
+            .border_type(ratatui::widgets::BorderType::Rounded);
+
+        StatefulWidget::render(
+            List::new(self.results)
+                .block(block)
+                .highlight_style(SELECTED_STYLE)
+                .highlight_spacing(ratatui::widgets::HighlightSpacing::Always),
+            area,
+            buf,
+            state,
+        )
+    }
+}
+
+pub struct Preview {
+    title: String,
+    lines: Vec<String>,
+    highlight: Option<usize>,
+}
+
+impl Preview {
+    pub fn new(title: String, lines: Vec<String>, highlight: Option<usize>) -> Self {
+        Self {
+            title,
+            lines,
+            highlight,
+        }
+    }
+}
+
+impl Widget for Preview {
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let block = Block::bordered()
+            .title(self.title)
+            .border_type(ratatui::widgets::BorderType::Rounded);
+
+        let lines: Vec<Line> = self
+            .lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, l)| {
+                if Some(i) == self.highlight {
+                    Line::styled(l, SELECTED_STYLE)
+                } else {
+                    Line::raw(l)
+                }
+            })
+            .collect();
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+pub struct TabBar {
+    titles: Vec<String>,
+    selected: usize,
+}
+
+impl TabBar {
+    pub fn new(titles: Vec<String>, selected: usize) -> Self {
+        Self { titles, selected }
+    }
+}
+
+impl Widget for TabBar {
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        Tabs::new(self.titles)
+            .select(self.selected)
+            .highlight_style(SELECTED_STYLE)
+            .render(area, buf);
+    }
+}
+
+pub struct ProgressLine {
+    label: String,
+    ratio: f64,
+}
+
+impl ProgressLine {
+    pub fn new(label: String, done: usize, total: usize) -> Self {
+        let ratio = if total == 0 {
+            0.0
+        } else {
+            done as f64 / total as f64
+        };
+
+        Self {
+            label,
+            ratio: ratio.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Widget for ProgressLine {
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        LineGauge::default()
+            .label(self.label)
+            .ratio(self.ratio)
+            .filled_style(MATCH_STYLE)
+            .unfilled_style(UNFOCUSED_STYLE)
+            .render(area, buf);
+    }
+}
+
+pub struct JobsPanel {
+    jobs: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl JobsPanel {
+    pub fn new(jobs: Vec<String>, errors: Vec<String>) -> Self {
+        Self { jobs, errors }
+    }
+}
+
+impl StatefulWidget for JobsPanel {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer, state: &mut Self::State)
+    where
+        Self: Sized,
+    {
+        let rect = OutputPopup::get_rect(&area);
+        Clear.render(rect, buf);
+
+        let [jobs_rect, errors_rect] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(rect);
+
+        let block = Block::bordered()
+            .title("jobs")
+            .title_bottom("(c) cancel (r) retry")
+            .border_type(ratatui::widgets::BorderType::Rounded);
+
+        StatefulWidget::render(
+            List::new(self.jobs)
+                .block(block)
+                .highlight_style(SELECTED_STYLE)
+                .highlight_spacing(ratatui::widgets::HighlightSpacing::Always),
+            jobs_rect,
+            buf,
+            state,
+        );
+
+        let block = Block::bordered()
+            .title(format!("errors ({})", self.errors.len()))
+            .border_type(ratatui::widgets::BorderType::Rounded);
+
+        Widget::render(List::new(self.errors).block(block), errors_rect, buf);
+    }
+}
+
+pub struct StatusBar {
+    info: Vec<String>,
+    notification: Option<(Level, String)>,
+}
+
+impl StatusBar {
+    pub fn new(info: Vec<String>, notification: Option<(Level, String)>) -> Self {
+        Self { info, notification }
+    }
+}
+
+impl Widget for StatusBar {
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        Line::styled(self.info.join(" │ "), UNFOCUSED_STYLE).render(area, buf);
+
+        if let Some((level, message)) = self.notification {
+            let style = match level {
+                Level::Success => Style::new().fg(GREEN.c400),
+                Level::Warning => Style::new().fg(YELLOW.c400),
+                Level::Error => Style::new().fg(RED.c400).add_modifier(Modifier::BOLD),
+            };
+
+            let width = (message.chars().count() as u16).min(area.width);
+            let rect = Rect {
+                x: area.right() - width,
+                width,
+                ..area
+            };
+
+            Clear.render(rect, buf);
+            Line::styled(message, style).render(rect, buf);
+        }
+    }
+}
+
+pub struct LogPopup<'a> {
+    title: String,
+    lines: &'a [String],
+    scroll_back: usize,
+}
+
+impl<'a> LogPopup<'a> {
+    /// `scroll_back` counts the lines hidden below the view, zero follows the end of the log.
+    pub fn new(title: String, lines: &'a [String], scroll_back: usize) -> Self {
+        Self {
+            title,
+            lines,
+            scroll_back,
+        }
+    }
+
+    /// How many lines fit in the popup drawn over `area`.
+    pub fn get_page_height(area: &Rect) -> usize {
+        OutputPopup::get_rect(area).height.saturating_sub(2) as usize
+    }
+}
+
+impl Widget for LogPopup<'_> {
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let rect = OutputPopup::get_rect(&area);
+        let height = Self::get_page_height(&area);
+
+        let end = self.lines.len().saturating_sub(self.scroll_back);
+        let start = end.saturating_sub(height);
+
+        let block = Block::bordered()
+            .title(self.title)
+            .title_bottom("(l) level")
+            .border_type(ratatui::widgets::BorderType::Rounded);
+        let p = Paragraph::new(self.lines[start..end].join("\n")).block(block);
+
+        Clear.render(rect, buf);
+        p.render(rect, buf);
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+
+/// Watches the directories on screen, collecting the ones changed by other programs.
+pub struct Watcher {
+    watcher: Option<RecommendedWatcher>,
+    receiver: Receiver<notify::Result<notify::Event>>,
+    watched: HashSet<PathBuf>,
+}
+
+impl std::fmt::Debug for Watcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watcher")
+            .field("watched", &self.watched)
+            .finish()
+    }
+}
+
+impl Default for Watcher {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        let watcher = match notify::recommended_watcher(sender) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                tracing::error!("Could not start the filesystem watcher: {}", e);
+                None
+            }
+        };
+
+        Self {
+            watcher,
+            receiver,
+            watched: HashSet::new(),
+        }
+    }
+}
+
+impl Watcher {
+    /// Watches exactly `paths`, dropping the directories no longer on screen.
+    pub fn watch(&mut self, paths: HashSet<PathBuf>) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+
+        for path in self.watched.difference(&paths) {
+            if let Err(e) = watcher.unwatch(path) {
+                tracing::debug!("Could not stop watching {:?}: {}", path, e);
+            }
+        }
+
+        let mut watched = HashSet::new();
+        for path in paths {
+            if !self.watched.contains(&path) {
+                if let Err(e) = watcher.watch(&path, RecursiveMode::NonRecursive) {
+                    tracing::debug!("Could not watch {:?}: {}", path, e);
+                    continue;
+                }
+            }
+            watched.insert(path);
+        }
+
+        self.watched = watched;
+    }
+
+    /// The directories whose content changed since the last call.
+    pub fn receive(&mut self) -> HashSet<PathBuf> {
+        let mut changed = HashSet::new();
+
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Ok(event)) if !event.kind.is_access() => {
+                    for path in event.paths {
+                        if let Some(parent) = path.parent() {
+                            changed.insert(parent.to_path_buf());
+                        }
+                        changed.insert(path);
+                    }
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::error!("Filesystem watcher error: {}", e),
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => return changed,
+            }
+        }
+    }
+}